@@ -29,8 +29,9 @@ impl App {
         }
     }
 
-    pub fn add_addon_inferred(&mut self, addon: Downloadable) -> Result<()> {
-        let addon_type = match self.server.jar.get_software_type() {
+    /// Mods for modded servers, plugins otherwise, asks if the software is unknown
+    pub fn infer_addon_type(&self) -> Result<AddonType> {
+        Ok(match self.server.jar.get_software_type() {
             SoftwareType::Modded => AddonType::Mod,
             SoftwareType::Normal | SoftwareType::Proxy => AddonType::Plugin,
             SoftwareType::Unknown => self.select(
@@ -40,9 +41,59 @@ impl App {
                     SelectItem(AddonType::Plugin, Cow::Borrowed("Plugin")),
                 ],
             )?,
+        })
+    }
+
+    pub fn add_addon_inferred(&mut self, addon: Downloadable) -> Result<AddonType> {
+        let addon_type = self.infer_addon_type()?;
+
+        self.add_addon(addon_type, addon)?;
+
+        Ok(addon_type)
+    }
+
+    /// Offer to add the required dependencies of `addon` that aren't added yet
+    pub async fn add_dependencies_of(
+        &mut self,
+        addon_type: AddonType,
+        addon: &Downloadable,
+    ) -> Result<()> {
+        let deps = match addon.required_dependencies(self).await {
+            Ok(deps) => deps,
+            Err(e) => {
+                self.warn(format!(
+                    "Couldn't check the dependencies of {}: {e}",
+                    addon.to_short_string()
+                ));
+                return Ok(());
+            }
         };
 
-        self.add_addon(addon_type, addon)
+        for dep in deps {
+            if dep.is_satisfied_by(&self.get_addons(addon_type)) {
+                continue;
+            }
+
+            let Some(dl) = dep.downloadable else {
+                self.warn(format!(
+                    "{} requires {}, which has to be added manually",
+                    addon.to_short_string(),
+                    dep.name
+                ));
+                continue;
+            };
+
+            if self.confirm(&format!(
+                "{} requires {}, add it too?",
+                addon.to_short_string(),
+                dep.name
+            ))? {
+                self.add_addon(addon_type, dl)?;
+                self.notify(Prefix::Imported, dep.name);
+            }
+        }
+
+        Ok(())
     }
 
     pub fn add_addon(&mut self, addon_type: AddonType, addon: Downloadable) -> Result<()> {
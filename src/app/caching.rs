@@ -1,14 +1,31 @@
 use std::{
+    collections::BTreeMap,
     fs::File,
-    io::{BufReader, BufWriter},
+    io::{BufRead, BufReader, BufWriter, Write},
     path::PathBuf,
 };
 
 use anyhow::{Context, Result};
-use serde::de::DeserializeOwned;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use tempfile::NamedTempFile;
+
+use super::{App, CacheStrategy, ResolvedFile};
+
+/// Namespace of the content-addressed object store, objects are stored as `objects/sha256/ab/abcdef...`
+/// or `objects/sha512/...`
+pub const OBJECTS_NAMESPACE: &str = "objects";
+/// Namespace of the index that maps `(namespace, key)` of `CacheStrategy::Indexed` to an object
+pub const INDEX_NAMESPACE: &str = "index";
 
 pub struct Cache(pub PathBuf);
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct IndexEntry {
+    pub key: String,
+    pub hash_name: String,
+    pub hash: String,
+}
+
 impl Cache {
     pub fn cache_root() -> Option<PathBuf> {
         Some(dirs::cache_dir()?.join("mcman"))
@@ -19,6 +36,136 @@ impl Cache {
         Some(Self(dir))
     }
 
+    /// Hash objects are stored by, sha512 if the source provides it so other sources
+    /// with the same hash can find the object before downloading
+    pub fn object_hash_name(hashes: &BTreeMap<String, String>) -> &'static str {
+        if hashes.contains_key("sha512") {
+            "sha512"
+        } else {
+            "sha256"
+        }
+    }
+
+    /// Path of an object in the content-addressed store
+    pub fn object_path(hash_name: &str, hash: &str) -> Option<PathBuf> {
+        Some(
+            Self::cache_root()?
+                .join(OBJECTS_NAMESPACE)
+                .join(hash_name)
+                .join(hash.get(..2)?)
+                .join(hash),
+        )
+    }
+
+    /// Path of the index entry for `key` in `namespace`
+    pub fn index_path(namespace: &str, key: &str) -> Option<PathBuf> {
+        Some(
+            Self::cache_root()?
+                .join(INDEX_NAMESPACE)
+                .join(namespace)
+                .join(format!("{}.json", App::hash_sha256(key))),
+        )
+    }
+
+    pub fn get_index_entry(namespace: &str, key: &str) -> Option<IndexEntry> {
+        let file = File::open(Self::index_path(namespace, key)?).ok()?;
+        let entry: IndexEntry = serde_json::from_reader(BufReader::new(file)).ok()?;
+
+        // sha256 collisions are not a concern but a wrong key means a broken index
+        (entry.key == key).then_some(entry)
+    }
+
+    pub fn write_index_entry(namespace: &str, entry: &IndexEntry) -> Result<()> {
+        let path = Self::index_path(namespace, &entry.key).context("No cache directory")?;
+        let dir = path.parent().unwrap();
+        std::fs::create_dir_all(dir)?;
+
+        // written next to it and renamed, parallel downloads might store the same entry
+        let mut tmp = NamedTempFile::new_in(dir).context("Creating temporary index entry")?;
+        tmp.write_all(&serde_json::to_vec(entry)?)?;
+        tmp.persist(&path)
+            .map_err(|e| e.error)
+            .context(format!("Creating index entry at: {}", path.display()))?;
+
+        Ok(())
+    }
+
+    /// `Indexed` files of sources that used `CacheStrategy::File` before were stored
+    /// at `{namespace}/{key}`. Moves such a file into the object store, if there is one
+    pub fn migrate_legacy_file(
+        namespace: &str,
+        key: &str,
+        hash_name: &str,
+    ) -> Result<Option<PathBuf>> {
+        let legacy = Self::get_cache(namespace)
+            .context("No cache directory")?
+            .path(key);
+        if !legacy.is_file() {
+            return Ok(None);
+        }
+
+        let mut hasher = App::create_hasher(hash_name);
+        let mut reader = BufReader::new(File::open(&legacy)?);
+        loop {
+            let chunk = reader.fill_buf()?;
+            if chunk.is_empty() {
+                break;
+            }
+
+            hasher.update(chunk);
+            let len = chunk.len();
+            reader.consume(len);
+        }
+
+        let hash = hex::encode(hasher.finalize());
+        let object = Self::object_path(hash_name, &hash).context("No cache directory")?;
+        std::fs::create_dir_all(object.parent().unwrap())?;
+        std::fs::rename(&legacy, &object).context(format!(
+            "Moving '{}' into the object store",
+            legacy.display()
+        ))?;
+
+        Self::write_index_entry(
+            namespace,
+            &IndexEntry {
+                key: key.to_owned(),
+                hash_name: hash_name.to_owned(),
+                hash,
+            },
+        )?;
+
+        Ok(Some(object))
+    }
+
+    /// Where the cached copy of a resolved file is (or would be) stored
+    ///
+    /// For `Indexed`, the hashes of the resolved file are preferred over the index.
+    /// This is `None` if the content hash isn't known yet
+    pub fn locate(resolved: &ResolvedFile) -> Option<PathBuf> {
+        match &resolved.cache {
+            CacheStrategy::File { namespace, path } => Some(Self::get_cache(namespace)?.path(path)),
+            CacheStrategy::Indexed { namespace, key } => {
+                let hash_name = Self::object_hash_name(&resolved.hashes);
+
+                // the index might still point to an older version of the file,
+                // it's only used if the hashes of the resolved file agree with it
+                let entry = Self::get_index_entry(namespace, key).filter(|entry| {
+                    match resolved.hashes.get(&entry.hash_name) {
+                        Some(hash) => *hash == entry.hash,
+                        None => !resolved.hashes.contains_key(hash_name),
+                    }
+                });
+
+                if let Some(entry) = entry {
+                    Self::object_path(&entry.hash_name, &entry.hash)
+                } else {
+                    Self::object_path(hash_name, resolved.hashes.get(hash_name)?)
+                }
+            }
+            CacheStrategy::None => None,
+        }
+    }
+
     pub fn get_json<T: DeserializeOwned>(&self, path: &str) -> Result<T> {
         let file = File::open(self.0.join(path))?;
         let reader = BufReader::new(file);
@@ -1,4 +1,10 @@
-use std::{borrow::Cow, fmt::Debug, fs, path::PathBuf, time::Duration};
+use std::{
+    borrow::Cow,
+    fmt::Debug,
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
 
 use anyhow::{bail, Context, Result};
 use digest::{Digest, DynDigest};
@@ -6,13 +12,54 @@ use indicatif::{ProgressBar, ProgressStyle};
 use md5::Md5;
 use sha1::Sha1;
 use sha2::{Sha256, Sha512};
-use tokio::{fs::File, io::BufWriter};
+use tempfile::NamedTempFile;
+use thiserror::Error;
+use tokio::{fs::File, io::BufWriter, time::sleep};
 use tokio_stream::StreamExt;
 use tokio_util::io::ReaderStream;
 
 use crate::util::SelectItem;
 
-use super::{App, CacheStrategy, Prefix, ProgressPrefix, Resolvable, ResolvedFile};
+use super::{
+    App, Cache, CacheStrategy, IndexEntry, Prefix, ProgressPrefix, Resolvable, ResolvedFile,
+    OBJECTS_NAMESPACE,
+};
+
+/// Retries back off exponentially from 1s up to this
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Error)]
+pub enum DownloadError {
+    #[error("Mismatched {hash_name} hash for '{filename}'\n  expected: {expected}\n  actual:   {actual}")]
+    HashMismatch {
+        filename: String,
+        hash_name: String,
+        expected: String,
+        actual: String,
+    },
+}
+
+impl DownloadError {
+    /// Whether trying again might fix the error
+    fn is_transient(err: &anyhow::Error) -> bool {
+        if err.downcast_ref::<Self>().is_some() {
+            return true;
+        }
+
+        match err.downcast_ref::<reqwest::Error>() {
+            Some(e) => {
+                e.is_timeout()
+                    || e.is_connect()
+                    || e.is_body()
+                    || e.is_decode()
+                    || e.status().is_some_and(|s| {
+                        s.is_server_error() || s == reqwest::StatusCode::TOO_MANY_REQUESTS
+                    })
+            }
+            None => false,
+        }
+    }
+}
 
 struct Bomb<T: FnMut()>(pub bool, pub T);
 
@@ -44,6 +91,13 @@ impl App {
         progress_bar.set_message(resolvable.to_string());
         progress_bar.enable_steady_tick(Duration::from_millis(250));
 
+        if self.config.offline && !resolvable.is_resolved() {
+            bail!(
+                "Can't resolve {} in offline mode, it isn't in a lockfile",
+                resolvable.to_string()
+            );
+        }
+
         let resolved = resolvable
             .resolve_source(self)
             .await
@@ -51,18 +105,86 @@ impl App {
 
         self.download_resolved(resolved, destination, progress_bar)
             .await
+            .context(format!("Downloading {}", resolvable.to_string()))
     }
 
-    pub fn resolve_cached_file(&self, cache: &CacheStrategy) -> Option<(PathBuf, bool)> {
-        match cache {
+    pub fn resolve_cached_file(&self, resolved: &ResolvedFile) -> Option<(PathBuf, bool)> {
+        match &resolved.cache {
             CacheStrategy::File { namespace, path } => self
                 .get_cache(namespace)
                 .map(|cache| (cache.path(path), cache.exists(path))),
-            CacheStrategy::Indexed { .. } => todo!(),
+            CacheStrategy::Indexed { namespace, key } => {
+                self.get_cache(namespace)?;
+                self.get_cache(OBJECTS_NAMESPACE)?;
+
+                if let Some(path) = Cache::locate(resolved).filter(|path| path.exists()) {
+                    return Some((path, true));
+                }
+
+                let hash_name = Cache::object_hash_name(&resolved.hashes);
+                match Cache::migrate_legacy_file(namespace, key, hash_name) {
+                    Ok(Some(object)) => {
+                        self.dbg(format!("moved {key} into {}", object.display()));
+                        return Some((object, true));
+                    }
+                    Ok(None) => {}
+                    Err(e) => {
+                        self.warn(format!("Couldn't move {key} into the object store: {e:#}"));
+                    }
+                }
+
+                // content hash isn't known until its downloaded, see `store_indexed`.
+                // only the folder is used, downloads go to unique temporary files in it
+                Some((
+                    Cache::cache_root()?
+                        .join(OBJECTS_NAMESPACE)
+                        .join("tmp")
+                        .join(Self::hash_sha256(&format!("{namespace}/{key}"))),
+                    false,
+                ))
+            }
             CacheStrategy::None => None,
         }
     }
 
+    /// Move a downloaded `Indexed` file into the object store and record it in the index
+    ///
+    /// Returns the path of the object
+    async fn store_indexed(
+        &self,
+        resolved: &ResolvedFile,
+        downloaded: NamedTempFile,
+        hash_name: &str,
+        hash: String,
+    ) -> Result<PathBuf> {
+        let CacheStrategy::Indexed { namespace, key } = &resolved.cache else {
+            bail!("{} isn't stored in the object store", resolved.filename);
+        };
+
+        let object = Cache::object_path(hash_name, &hash).context("No cache directory")?;
+
+        // the same object might be stored by another download at the same time,
+        // renaming is atomic so either copy ends up there
+        tokio::fs::create_dir_all(object.parent().unwrap()).await?;
+        downloaded
+            .persist(&object)
+            .map_err(|e| e.error)
+            .context(format!("Moving downloaded file to '{}'", object.display()))?;
+
+        Cache::write_index_entry(
+            namespace,
+            &IndexEntry {
+                key: key.clone(),
+                hash_name: hash_name.to_owned(),
+                hash,
+            },
+        )?;
+
+        self.dbg(format!("stored {key} in {}", object.display()));
+
+        Ok(object)
+    }
+
     pub fn create_hasher(name: &str) -> Box<dyn DynDigest> {
         match name {
             "sha256" => Box::new(<Sha256 as Digest>::new()),
@@ -73,7 +195,39 @@ impl App {
         }
     }
 
-    #[allow(clippy::too_many_lines)]
+    /// The hash to verify `resolved` with, unless verification is disabled
+    fn get_verify_hash(&self, resolved: &ResolvedFile) -> Option<(String, String)> {
+        if self.config.downloads.verify {
+            Self::get_best_hash(&resolved.hashes)
+        } else {
+            None
+        }
+    }
+
+    fn validate_hash(
+        &self,
+        filename: &str,
+        hasher: Option<(String, Box<dyn DynDigest>, String)>,
+    ) -> Result<()> {
+        if let Some((hash_name, digest, expected)) = hasher {
+            let actual = hex::encode(digest.finalize());
+
+            if expected == actual {
+                self.dbg("hash check success");
+            } else {
+                return Err(DownloadError::HashMismatch {
+                    filename: filename.to_owned(),
+                    hash_name,
+                    expected,
+                    actual,
+                }
+                .into());
+            }
+        }
+
+        Ok(())
+    }
+
     pub async fn download_resolved(
         &self,
         resolved: ResolvedFile,
@@ -87,39 +241,6 @@ impl App {
         progress_bar.set_prefix(ProgressPrefix::Checking);
         progress_bar.enable_steady_tick(Duration::from_millis(250));
 
-        // Some(Path) if file exists in cache
-        let cached_file_path = self.resolve_cached_file(&resolved.cache);
-
-        let hasher = Self::get_best_hash(&resolved.hashes);
-
-        // if resolved has hashes, Some((hash name, dyndigest, hash value))
-        let mut hasher = hasher.map(|(name, hash)| {
-            let digester: Box<dyn DynDigest> = App::create_hasher(&name);
-
-            (name, digester, hash)
-        });
-
-        let validate_hash = |hasher: Option<(String, Box<dyn DynDigest>, String)>| {
-            if let Some((hash_name, digest, resolved_hash)) = hasher {
-                let stream_hash = hex::encode(digest.finalize());
-
-                if resolved_hash == stream_hash {
-                    self.dbg("hash check success");
-                } else {
-                    // TODO: skipping checks etc
-                    // also pretty msg
-                    bail!(
-                        "Mismatched hash!
-                    Type: {hash_name}
-                    Expected hash: {resolved_hash}
-                    Real hash: {stream_hash}"
-                    );
-                }
-            }
-
-            Ok(())
-        };
-
         // dest. file path
         let file_path = destination.join(&resolved.filename);
 
@@ -166,9 +287,21 @@ impl App {
                     true
                 };
 
-                // TODO: optionally check hashes for existing file
+                let hash_matches = match self.get_verify_hash(&resolved) {
+                    Some((hash_name, expected)) if size_matches => {
+                        let actual = Self::hash_file(&file_path, &hash_name).await?;
+                        if actual != expected {
+                            self.warn(format!(
+                                "Existing file '{}' has a wrong {hash_name} hash, downloading again",
+                                resolved.filename
+                            ));
+                        }
+                        actual == expected
+                    }
+                    _ => true,
+                };
 
-                if size_matches {
+                if size_matches && hash_matches {
                     // file already there and is ok
                     self.notify(Prefix::Skipped, progress_bar.message());
 
@@ -177,7 +310,71 @@ impl App {
             }
         }
 
-        let target_file = File::create(&file_path).await.context(format!(
+        let retries = self.config.downloads.retries;
+        let mut attempt = 0;
+
+        loop {
+            match self
+                .try_download_resolved(&resolved, &file_path, &progress_bar)
+                .await
+            {
+                Ok(()) => break,
+                Err(e) if attempt < retries && DownloadError::is_transient(&e) => {
+                    attempt += 1;
+                    let delay = Duration::from_millis(
+                        500_u64.saturating_mul(2_u64.saturating_pow(attempt)),
+                    )
+                    .min(MAX_RETRY_DELAY);
+
+                    self.warn(format!(
+                        "{e}\n  retrying '{}' in {:.1}s ({attempt}/{retries})",
+                        resolved.filename,
+                        delay.as_secs_f32()
+                    ));
+
+                    progress_bar.set_style(ProgressStyle::with_template(
+                        "{spinner:.blue} {prefix} {msg}...",
+                    )?);
+                    progress_bar.set_prefix(ProgressPrefix::Checking);
+                    progress_bar.set_position(0);
+                    progress_bar.enable_steady_tick(Duration::from_millis(250));
+
+                    sleep(delay).await;
+                }
+                Err(e) => {
+                    return Err(e.context(format!(
+                        "Failed to download '{}' after {} attempt(s)",
+                        resolved.filename,
+                        attempt + 1
+                    )));
+                }
+            }
+        }
+
+        progress_bar.finish_and_clear();
+
+        Ok(resolved)
+    }
+
+    /// A single attempt of downloading (or copying from cache) a file
+    #[allow(clippy::too_many_lines)]
+    async fn try_download_resolved(
+        &self,
+        resolved: &ResolvedFile,
+        file_path: &Path,
+        progress_bar: &ProgressBar,
+    ) -> Result<()> {
+        // Some(Path) if file exists in cache
+        let cached_file_path = self.resolve_cached_file(resolved);
+
+        // if resolved has hashes, Some((hash name, dyndigest, hash value))
+        let mut hasher = self.get_verify_hash(resolved).map(|(name, hash)| {
+            let digester: Box<dyn DynDigest> = App::create_hasher(&name);
+
+            (name, digester, hash)
+        });
+
+        let target_file = File::create(file_path).await.context(format!(
             "Creating destination file at '{}'",
             file_path.to_string_lossy()
         ))?;
@@ -185,7 +382,7 @@ impl App {
         // this bomb will explode (delete target_file) if its not defused (fn exits with Err)
         let mut bomb = Bomb(true, || {
             // i mean, atleast try right
-            let _ = fs::remove_file(&file_path);
+            let _ = fs::remove_file(file_path);
         });
 
         if let Some((cached, cached_size)) = match &cached_file_path {
@@ -221,6 +418,16 @@ impl App {
             progress_bar.set_length(cached_size);
             progress_bar.set_prefix(ProgressPrefix::Copying);
 
+            // objects in the store can always be checked using their index entry
+            if hasher.is_none() && self.config.downloads.verify {
+                if let CacheStrategy::Indexed { namespace, key } = &resolved.cache {
+                    hasher = Cache::get_index_entry(namespace, key).map(|entry| {
+                        let digester = App::create_hasher(&entry.hash_name);
+                        (entry.hash_name, digester, entry.hash)
+                    });
+                }
+            }
+
             let cache_file = File::open(&cached).await.context(format!(
                 "Opening file '{}' from cache dir",
                 cached.to_string_lossy()
@@ -248,12 +455,31 @@ impl App {
                 progress_bar.inc(item.len() as u64);
             }
 
-            // TODO: retry downloading if fails
-            validate_hash(hasher)?;
+            if let Err(e) = self.validate_hash(&resolved.filename, hasher) {
+                // the cached file is corrupt, remove it so the next attempt downloads it again
+                self.warn(format!(
+                    "Cached file '{}' is corrupted, deleting it",
+                    cached.to_string_lossy()
+                ));
+                let _ = tokio::fs::remove_file(&cached).await;
+                return Err(e);
+            }
+
+            // keep track of when the file was last used, see `mcman cache prune`
+            if let Ok(file) = fs::File::options().write(true).open(cached) {
+                let _ = file.set_modified(SystemTime::now());
+            }
 
             progress_bar.finish_and_clear();
             self.notify(Prefix::Copied, &resolved.filename);
         } else {
+            if self.config.offline {
+                bail!(
+                    "'{}' isn't cached, can't download it in offline mode",
+                    resolved.filename
+                );
+            }
+
             progress_bar.set_prefix(ProgressPrefix::Fetching);
             progress_bar.set_message(resolved.filename.clone());
 
@@ -287,15 +513,32 @@ impl App {
             )?);
             progress_bar.set_prefix(ProgressPrefix::Downloading);
 
-            // if file can be cached, BufWriter to the file in cache dir
-            // otherwise BufWriter to output file
-            let mut file_writer = BufWriter::new(if let Some((path, _exists)) = cached_file_path {
-                tokio::fs::create_dir_all(path.parent().unwrap()).await?;
-                File::create(path).await?
+            // if file can be cached, BufWriter to a temporary file in the cache dir which
+            // is moved into place when it's complete, so parallel downloads of the same
+            // file don't write to the same file. otherwise BufWriter to output file
+            let cache_path = cached_file_path.map(|(path, _exists)| path);
+            let cache_tmp = if let Some(path) = &cache_path {
+                let dir = path.parent().unwrap();
+                tokio::fs::create_dir_all(dir).await?;
+                Some(NamedTempFile::new_in(dir).context("Creating temporary file in cache")?)
+            } else {
+                None
+            };
+            let mut file_writer = BufWriter::new(if let Some(tmp) = &cache_tmp {
+                File::from_std(tmp.reopen()?)
             } else {
                 target_file
             });
 
+            // content hash of files going into the object store
+            let mut object_hasher = match &resolved.cache {
+                CacheStrategy::Indexed { .. } if cache_tmp.is_some() => {
+                    let hash_name = Cache::object_hash_name(&resolved.hashes);
+                    Some((hash_name, App::create_hasher(hash_name)))
+                }
+                _ => None,
+            };
+
             let mut stream = response.bytes_stream();
             while let Some(item) = stream.next().await {
                 let item = item?;
@@ -304,6 +547,10 @@ impl App {
                     digest.update(&item);
                 }
 
+                if let Some((_, ref mut digest)) = object_hasher {
+                    digest.update(&item);
+                }
+
                 tokio::io::copy(&mut item.as_ref(), &mut file_writer)
                     .await
                     .context("Writing downloaded chunk")?;
@@ -311,23 +558,35 @@ impl App {
                 progress_bar.inc(item.len() as u64);
             }
 
-            validate_hash(hasher)?;
+            tokio::io::AsyncWriteExt::flush(&mut file_writer).await?;
+            drop(file_writer);
+
+            self.validate_hash(&resolved.filename, hasher)?;
+
+            // dropping the temporary file deletes it if anything above failed
+            let cache_path = match (cache_tmp, cache_path, object_hasher) {
+                (Some(tmp), _, Some((hash_name, digest))) => {
+                    let hash = hex::encode(digest.finalize());
+                    Some(self.store_indexed(resolved, tmp, hash_name, hash).await?)
+                }
+                (Some(tmp), Some(path), None) => {
+                    tmp.persist(&path)
+                        .map_err(|e| e.error)
+                        .context(format!("Moving downloaded file to '{}'", path.display()))?;
+                    Some(path)
+                }
+                _ => None,
+            };
 
             // if we downloaded to cache instead of output above, copy the file to output
             // small todo: maybe write to both while downloading?
-            if let Some(cached_file_path) = match &resolved.cache {
-                CacheStrategy::File { namespace, path } => {
-                    self.get_cache(namespace).map(|c| c.path(path))
-                }
-                CacheStrategy::Indexed { .. } => todo!(),
-                CacheStrategy::None => None,
-            } {
+            if let Some(cached_file_path) = &cache_path {
                 progress_bar.set_style(ProgressStyle::with_template(
                     "{spinner:.blue} {prefix} {msg}...",
                 )?);
                 progress_bar.set_prefix(ProgressPrefix::Copying);
 
-                tokio::fs::copy(cached_file_path, &file_path).await?;
+                tokio::fs::copy(cached_file_path, file_path).await?;
             }
 
             progress_bar.finish_and_clear();
@@ -337,8 +596,6 @@ impl App {
         // succeeded, so defuse
         bomb.defuse();
 
-        progress_bar.finish_and_clear();
-
-        Ok(resolved)
+        Ok(())
     }
 }
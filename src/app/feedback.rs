@@ -135,7 +135,6 @@ impl App {
         self.notify(Prefix::Warning, message);
     }
 
-    #[allow(dead_code)]
     pub fn error<S: Display>(&self, message: S) {
         self.notify(Prefix::Error, message);
     }
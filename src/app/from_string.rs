@@ -1,4 +1,4 @@
-use std::{borrow::Cow, path::Path};
+use std::{borrow::Cow, collections::BTreeMap, path::Path};
 
 use anyhow::{bail, Context, Result};
 
@@ -316,6 +316,7 @@ impl App {
                             url: urlstr.to_owned(),
                             filename: Some(input),
                             desc: if desc.is_empty() { None } else { Some(desc) },
+                            hashes: BTreeMap::new(),
                         })
                     }
                     1 => {
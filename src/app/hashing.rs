@@ -2,7 +2,11 @@ use anyhow::{Context, Result};
 use digest::{Digest, DynDigest};
 use indicatif::ProgressBar;
 use sha2::Sha256;
-use std::{collections::BTreeMap, marker::Unpin, path::PathBuf};
+use std::{
+    collections::BTreeMap,
+    marker::Unpin,
+    path::{Path, PathBuf},
+};
 use tokio::{
     fs::File,
     io::{AsyncRead, AsyncWrite},
@@ -10,7 +14,7 @@ use tokio::{
 use tokio_stream::StreamExt;
 use tokio_util::io::ReaderStream;
 
-use super::{App, ResolvedFile};
+use super::{App, Cache, CacheStrategy, ResolvedFile};
 
 impl App {
     pub fn get_best_hash(hashes: &BTreeMap<String, String>) -> Option<(String, String)> {
@@ -25,11 +29,17 @@ impl App {
     pub async fn hash_resolved_file(&self, resolved: &ResolvedFile) -> Result<(String, String)> {
         if let Some(pair) = Self::get_best_hash(&resolved.hashes) {
             Ok(pair)
+        } else if let Some(entry) = match &resolved.cache {
+            CacheStrategy::Indexed { namespace, key } => Cache::get_index_entry(namespace, key),
+            _ => None,
+        } {
+            // already downloaded into the object store once
+            Ok((entry.hash_name, entry.hash))
         } else {
             // calculate hash manually
 
             let (file_path, is_temp) =
-                if let Some((path, true)) = self.resolve_cached_file(&resolved.cache) {
+                if let Some((path, true)) = self.resolve_cached_file(resolved) {
                     // file exists in cache dir
                     (path, false)
                 } else {
@@ -77,6 +87,22 @@ impl App {
         }
     }
 
+    /// Hash a file on disk using the given algorithm, returns the hex digest
+    pub async fn hash_file(path: &Path, hash_name: &str) -> Result<String> {
+        let mut digester = Self::create_hasher(hash_name);
+
+        let file = File::open(path)
+            .await
+            .context(format!("Opening file '{}'", path.display()))?;
+
+        let mut stream = ReaderStream::new(file);
+        while let Some(item) = stream.next().await {
+            digester.update(&item?);
+        }
+
+        Ok(hex::encode(digester.finalize()))
+    }
+
     pub async fn copy_with_hashing<R: AsyncRead + Unpin, W: AsyncWrite + Unpin>(
         source: &mut R,
         dest: &mut W,
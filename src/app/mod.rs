@@ -5,6 +5,7 @@ mod feedback;
 mod from_string;
 mod hashing;
 mod resolvable;
+mod secrets;
 
 use anyhow::{Context, Result};
 pub use caching::*;
@@ -12,6 +13,7 @@ use confique::Config;
 pub use feedback::*;
 use indicatif::MultiProgress;
 pub use resolvable::*;
+pub use secrets::*;
 
 use crate::model::{AppConfig, Downloadable, Network, Server};
 use crate::sources;
@@ -19,6 +21,7 @@ use crate::sources;
 use std::{
     env,
     fmt::{self, Display, Formatter},
+    sync::Arc,
 };
 
 pub const APP_USER_AGENT: &str = concat!(
@@ -86,6 +89,7 @@ pub struct App {
 
     pub multi_progress: MultiProgress,
     pub config: AppConfig,
+    pub secrets: Arc<SecretStore>,
 }
 
 impl App {
@@ -104,6 +108,7 @@ impl App {
                         .join("mcman/.mcman.toml"),
                 )
                 .load()?,
+            secrets: Arc::default(),
         })
     }
 
@@ -112,10 +117,29 @@ impl App {
     }
 
     pub fn reload_server(&mut self) -> Result<()> {
-        self.server = Server::load().context("Loading server.toml")?;
+        let path = self.server.path.join("server.toml");
+
+        self.server = if path.exists() {
+            Server::load_from(&path)
+        } else {
+            Server::load()
+        }
+        .context("Loading server.toml")?;
         Ok(())
     }
 
+    /// A copy of this app for another server of the network
+    pub fn for_server(&self, server: Server) -> Self {
+        Self {
+            http_client: self.http_client.clone(),
+            server,
+            network: self.network.clone(),
+            multi_progress: self.multi_progress.clone(),
+            config: self.config.clone(),
+            secrets: self.secrets.clone(),
+        }
+    }
+
     pub fn reload_network(&mut self) -> Result<()> {
         self.network = Network::load().context("Loading network.toml")?;
         Ok(())
@@ -178,6 +202,45 @@ impl App {
 
     pub fn var<I: AsRef<str>>(&self, var: I) -> Option<String> {
         let k = var.as_ref();
+        self.builtin_var(k).or_else(|| {
+            if let Some(v) = self.env_var(k) {
+                Some(v)
+            } else if k.starts_with("NW_") {
+                if let Some(nw) = &self.network {
+                    if k.starts_with("NW_SERVER_") {
+                        let (name, ty) = k.strip_prefix("NW_SERVER_").unwrap().split_once('_')?;
+
+                        let serv = nw.servers.get(name)?;
+
+                        let ip = env::var(format!("IP_{name}"))
+                            .ok()
+                            .or(serv.ip_address.clone())
+                            .unwrap_or("127.0.0.1".to_owned());
+
+                        let port = env::var(format!("PORT_{name}"))
+                            .ok()
+                            .unwrap_or(serv.port.to_string());
+
+                        match ty.to_lowercase().as_str() {
+                            "ip" => Some(ip),
+                            "port" => Some(port),
+                            "address" => Some(format!("{ip}:{port}")),
+                            _ => None,
+                        }
+                    } else {
+                        nw.variables.get(k.strip_prefix("NW_").unwrap()).cloned()
+                    }
+                } else {
+                    None
+                }
+            } else {
+                self.server.variables.get(k).cloned()
+            }
+        })
+    }
+
+    /// Variables that mcman defines itself, these can't be overridden
+    pub fn builtin_var(&self, k: &str) -> Option<String> {
         match k {
             "SERVER_NAME" => Some(self.server.name.clone()),
             "SERVER_VERSION" | "mcver" | "mcversion" => Some(self.server.mc_version.clone()),
@@ -249,41 +312,11 @@ impl App {
 
             _ => None,
         }
-        .or_else(|| {
-            if let Ok(v) = std::env::var(k) {
-                Some(v)
-            } else if k.starts_with("NW_") {
-                if let Some(nw) = &self.network {
-                    if k.starts_with("NW_SERVER_") {
-                        let (name, ty) = k.strip_prefix("NW_SERVER_").unwrap().split_once('_')?;
-
-                        let serv = nw.servers.get(name)?;
-
-                        let ip = env::var(format!("IP_{name}"))
-                            .ok()
-                            .or(serv.ip_address.clone())
-                            .unwrap_or("127.0.0.1".to_owned());
-
-                        let port = env::var(format!("PORT_{name}"))
-                            .ok()
-                            .unwrap_or(serv.port.to_string());
+    }
 
-                        match ty.to_lowercase().as_str() {
-                            "ip" => Some(ip),
-                            "port" => Some(port),
-                            "address" => Some(format!("{ip}:{port}")),
-                            _ => None,
-                        }
-                    } else {
-                        nw.variables.get(k.strip_prefix("NW_").unwrap()).cloned()
-                    }
-                } else {
-                    None
-                }
-            } else {
-                self.server.variables.get(k).cloned()
-            }
-        })
+    /// A value from the environment or the `.env` file
+    pub fn env_var(&self, k: &str) -> Option<String> {
+        env::var(k).ok().or_else(|| self.dotenv_var(k))
     }
 
     pub fn get_cache(&self, ns: &str) -> Option<Cache> {
@@ -347,6 +380,7 @@ impl App {
 
     interop_methods! {
         markdown => MarkdownAPI,
+        java => JavaAPI,
         worlds => WorldsAPI,
         hooks => HooksAPI,
     }
@@ -1,4 +1,4 @@
-use std::{borrow::Cow, collections::BTreeMap};
+use std::{borrow::Cow, collections::BTreeMap, fmt::Display};
 
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
@@ -7,6 +7,11 @@ use super::App;
 
 pub trait Resolvable {
     async fn resolve_source(&self, app: &App) -> Result<ResolvedFile>;
+
+    /// Whether `resolve_source` can be called without network access
+    fn is_resolved(&self) -> bool {
+        false
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -18,6 +23,23 @@ pub struct ResolvedFile {
     pub hashes: BTreeMap<String, String>,
 }
 
+/// Already resolved, used when reusing entries from a lockfile
+impl Resolvable for ResolvedFile {
+    async fn resolve_source(&self, _app: &App) -> Result<ResolvedFile> {
+        Ok(self.clone())
+    }
+
+    fn is_resolved(&self) -> bool {
+        true
+    }
+}
+
+impl Display for ResolvedFile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.filename)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(tag = "type")]
 pub enum CacheStrategy {
@@ -25,10 +47,10 @@ pub enum CacheStrategy {
         namespace: Cow<'static, str>,
         path: String,
     },
+    /// Stored once in the content-addressed object store, `key` identifies the file in `namespace`
     Indexed {
-        index_path: String,
+        namespace: Cow<'static, str>,
         key: String,
-        value: String,
     },
     #[default]
     None,
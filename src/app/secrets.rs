@@ -0,0 +1,163 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Debug, Formatter},
+    io::{ErrorKind, Write},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    sync::Mutex,
+};
+
+use anyhow::{bail, Context, Result};
+
+use super::App;
+
+pub const DOTENV_FILE: &str = ".env";
+pub const SECRETS_FILE: &str = "secrets.toml.age";
+
+type Values = HashMap<String, String>;
+
+/// Values of `.env` and `secrets.toml.age` files, loaded once per folder
+#[derive(Default)]
+pub struct SecretStore {
+    dotenv: Mutex<HashMap<PathBuf, Values>>,
+    encrypted: Mutex<HashMap<PathBuf, Values>>,
+}
+
+// never print the values
+impl Debug for SecretStore {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("SecretStore { .. }")
+    }
+}
+
+fn get_cached(
+    cache: &Mutex<HashMap<PathBuf, Values>>,
+    folder: &Path,
+    key: &str,
+    load: impl FnOnce() -> Values,
+) -> Option<String> {
+    cache
+        .lock()
+        .unwrap()
+        .entry(folder.to_path_buf())
+        .or_insert_with(load)
+        .get(key)
+        .cloned()
+}
+
+/// Parse a dotenv file: `KEY=value` lines, `export` and quotes are allowed
+fn parse_dotenv(content: &str) -> Values {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| line.strip_prefix("export ").unwrap_or(line))
+        .filter_map(|line| line.split_once('='))
+        .map(|(k, v)| {
+            let v = v.trim();
+            let v = ['"', '\'']
+                .into_iter()
+                .find_map(|q| v.strip_prefix(q).and_then(|v| v.strip_suffix(q)))
+                .unwrap_or(v);
+
+            (k.trim().to_owned(), v.to_owned())
+        })
+        .collect()
+}
+
+impl App {
+    /// The server folder, then the network folder
+    fn secret_folders(&self) -> Vec<PathBuf> {
+        let mut folders = vec![self.server.path.clone()];
+        folders.extend(self.network.as_ref().map(|nw| nw.path.clone()));
+        folders
+    }
+
+    /// A value from the `.env` file of the server or the network
+    pub fn dotenv_var(&self, key: &str) -> Option<String> {
+        self.secret_folders().iter().find_map(|folder| {
+            get_cached(&self.secrets.dotenv, folder, key, || {
+                std::fs::read_to_string(folder.join(DOTENV_FILE))
+                    .map(|content| parse_dotenv(&content))
+                    .unwrap_or_default()
+            })
+        })
+    }
+
+    /// Value of `${secret:NAME}`, from environment variables, `.env` files or
+    /// the `secrets.toml.age` of the server or the network
+    pub fn secret(&self, name: &str) -> Option<String> {
+        std::env::var(name)
+            .ok()
+            .or_else(|| self.dotenv_var(name))
+            .or_else(|| {
+                self.secret_folders().iter().find_map(|folder| {
+                    get_cached(&self.secrets.encrypted, folder, name, || {
+                        let path = folder.join(SECRETS_FILE);
+                        if !path.exists() {
+                            return Values::new();
+                        }
+
+                        self.decrypt_secrets(&path).unwrap_or_else(|e| {
+                            self.warn(format!("Couldn't decrypt {}: {e:#}", path.display()));
+                            Values::new()
+                        })
+                    })
+                })
+            })
+    }
+
+    /// Decrypt an age encrypted toml file with the `age` binary. The identity
+    /// is passed through stdin so the private key never touches the disk
+    fn decrypt_secrets(&self, path: &Path) -> Result<Values> {
+        let Some(key) = &self.config.secrets.key else {
+            bail!("MCMAN_SECRETS_KEY isn't set");
+        };
+        let age_bin = &self.config.secrets.age_bin;
+
+        let spawned = Command::new(age_bin)
+            .arg("--decrypt")
+            .arg("--identity")
+            .arg("-")
+            .arg(path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn();
+
+        let mut child = match spawned {
+            Ok(child) => child,
+            Err(e) if e.kind() == ErrorKind::NotFound => bail!(
+                "'{age_bin}' not found, install age 1.1+ from https://age-encryption.org \
+                or set MCMAN_AGE_BIN"
+            ),
+            Err(e) => return Err(e).context(format!("Running {age_bin}")),
+        };
+
+        // dropped after writing so age sees the end of the identities
+        let mut stdin = child.stdin.take().context("Opening stdin of age")?;
+        writeln!(stdin, "{}", key.trim()).context("Passing the identity to age")?;
+        drop(stdin);
+
+        let output = child
+            .wait_with_output()
+            .context(format!("Running {age_bin}"))?;
+
+        if !output.status.success() {
+            bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+        }
+
+        let table: toml::Table = toml::from_str(
+            std::str::from_utf8(&output.stdout).context("Decrypted secrets aren't UTF-8")?,
+        )
+        .context("Parsing decrypted secrets")?;
+
+        Ok(table
+            .into_iter()
+            .map(|(k, v)| match v {
+                toml::Value::String(s) => (k, s),
+                v => (k, v.to_string()),
+            })
+            .collect())
+    }
+}
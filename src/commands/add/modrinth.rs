@@ -96,10 +96,13 @@ pub async fn run(mut app: App, args: Args) -> Result<()> {
             todo!("Modpack importing currently unsupported")
         }
         "mod" => {
-            app.add_addon_inferred(Downloadable::Modrinth {
+            let addon = Downloadable::Modrinth {
                 id: project.slug.clone(),
                 version: version.id.clone(),
-            })?;
+            };
+
+            let addon_type = app.add_addon_inferred(addon.clone())?;
+            app.add_dependencies_of(addon_type, &addon).await?;
 
             app.save_changes()?;
             app.notify(Prefix::Imported, format!("{} from modrinth", project.title));
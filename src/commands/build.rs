@@ -12,30 +12,75 @@ pub struct BuildArgs {
     /// Skip some stages
     #[arg(short, long, value_name = "stages")]
     skip: Vec<String>,
+    #[command(flatten)]
+    options: BuildOptions,
+}
+
+/// Build flags that are shared with `mcman network build`
+#[derive(clap::Args, Clone)]
+pub struct BuildOptions {
     #[arg(long)]
     /// Don't skip downloading already downloaded jars
     force: bool,
+    /// Reuse resolved files from mcman.lock for unchanged addons
+    #[arg(long)]
+    locked: bool,
+    /// Like --locked, but fail if server.toml doesn't match mcman.lock
+    #[arg(long)]
+    frozen: bool,
+    /// Fail if required dependencies of addons are missing or variables are undefined
+    #[arg(long)]
+    strict: bool,
+    /// Don't verify hashes of downloaded and cached files
+    #[arg(long)]
+    no_verify: bool,
+    /// Build only using lockfiles and the cache, without internet access
+    #[arg(long)]
+    offline: bool,
 }
 
-impl BuildArgs {
-    pub fn create_build_context(self, app: &mut App) -> Result<BuildContext<'_>> {
-        let default_output = app.server.path.join("server");
-        let output_dir = self.output.unwrap_or(default_output);
-
+impl BuildOptions {
+    pub fn create_build_context(
+        self,
+        app: &mut App,
+        output_dir: PathBuf,
+        skip_stages: Vec<String>,
+    ) -> Result<BuildContext<'_>> {
         std::fs::create_dir_all(&output_dir).context("Failed to create output directory")?;
 
+        if self.no_verify {
+            app.config.downloads.verify = false;
+        }
+
+        if self.offline {
+            app.config.offline = true;
+        }
+
         Ok(BuildContext {
             app,
             force: self.force,
-            skip_stages: self.skip,
+            locked: self.locked || self.frozen,
+            frozen: self.frozen,
+            strict: self.strict,
+            skip_stages,
             output_dir,
             lockfile: Lockfile::default(),
             new_lockfile: Lockfile::default(),
+            committed_lockfile: None,
             _server_process: None,
         })
     }
 }
 
+impl BuildArgs {
+    pub fn create_build_context(self, app: &mut App) -> Result<BuildContext<'_>> {
+        let default_output = app.server.path.join("server");
+        let output_dir = self.output.unwrap_or(default_output);
+
+        self.options.create_build_context(app, output_dir, self.skip)
+    }
+}
+
 pub async fn run(mut app: App, args: BuildArgs) -> Result<()> {
     let mut ctx = args.create_build_context(&mut app)?;
 
@@ -1,13 +1,26 @@
-use std::time::Duration;
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    env, io,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
 
 use anyhow::{bail, Context, Result};
 use console::style;
-use indicatif::{ProgressBar, ProgressStyle};
+use indicatif::{HumanBytes, ProgressBar, ProgressStyle};
+use walkdir::WalkDir;
+use zip::{write::FileOptions, ZipArchive, ZipWriter};
 
-use crate::app::Cache;
+use crate::{
+    app::{Cache, CacheStrategy, IndexEntry, INDEX_NAMESPACE, OBJECTS_NAMESPACE},
+    interop::java,
+    model::{Lockfile, COMMITTED_LOCKFILE},
+    util::{self, md::MarkdownTable},
+};
 use std::fs;
 
-#[derive(clap::Subcommand, Clone, Copy)]
+#[derive(clap::Subcommand, Clone)]
 pub enum Commands {
     /// Print cache root
     Path,
@@ -21,6 +34,204 @@ pub enum Commands {
     Open,
     /// Delete everything from cache (no confirmation)
     Clear,
+    /// Show disk usage per namespace
+    Stats,
+    /// Delete old or least recently used files
+    Prune {
+        /// Delete files that weren't used for this long (e.g. 30d, 12h)
+        #[arg(long, value_parser = util::parse_duration)]
+        older_than: Option<Duration>,
+        /// Delete least recently used files until the cache is smaller than this (e.g. 5G)
+        #[arg(long, value_parser = util::parse_size)]
+        max_size: Option<u64>,
+        /// Only print what would be deleted
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Delete files not used by the lockfiles of the given servers/networks
+    Gc {
+        /// Server/network folders or lockfiles (defaults to the current folder)
+        paths: Vec<PathBuf>,
+        /// Only print what would be deleted
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Restore cached files from a zip created by `mcman fetch --export`
+    Import {
+        /// The zip file
+        file: PathBuf,
+    },
+}
+
+struct CacheFile {
+    path: PathBuf,
+    namespace: String,
+    size: u64,
+    modified: SystemTime,
+}
+
+fn list_files(root: &Path) -> Result<Vec<CacheFile>> {
+    let mut files = vec![];
+
+    for entry in WalkDir::new(root) {
+        let entry = entry?;
+
+        if !entry.file_type().is_file() {
+            continue;
+        }
+
+        let meta = entry.metadata()?;
+        let namespace = entry
+            .path()
+            .strip_prefix(root)?
+            .components()
+            .next()
+            .map(|c| c.as_os_str().to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        files.push(CacheFile {
+            path: entry.path().to_path_buf(),
+            namespace,
+            size: meta.len(),
+            modified: meta.modified()?,
+        });
+    }
+
+    Ok(files)
+}
+
+/// Files prune and gc are allowed to delete. Installed java runtimes aren't
+/// in any lockfile and keep the dates of their archives, so they're left out
+fn deletable_files(root: &Path) -> Result<Vec<CacheFile>> {
+    Ok(list_files(root)?
+        .into_iter()
+        .filter(|f| f.namespace != java::CACHE_DIR)
+        .collect())
+}
+
+/// Cached api responses and manifests, kept by prune and gc. This doesn't
+/// check the namespace, index entries are json files too
+fn is_metadata(file: &CacheFile) -> bool {
+    file.path.extension().is_some_and(|ext| ext == "json")
+}
+
+/// The object an index entry points to
+fn index_entry_object(path: &Path) -> Option<PathBuf> {
+    let entry: IndexEntry = serde_json::from_str(&fs::read_to_string(path).ok()?).ok()?;
+    Cache::object_path(&entry.hash_name, &entry.hash)
+}
+
+fn find_lockfiles(path: &Path) -> Result<Vec<PathBuf>> {
+    if path.is_file() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+
+    let mut list = vec![];
+
+    for entry in WalkDir::new(path)
+        .into_iter()
+        .filter_entry(|e| e.file_name() != ".git")
+    {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy();
+
+        if entry.file_type().is_file() && (name == ".mcman.lock" || name == COMMITTED_LOCKFILE) {
+            list.push(entry.path().to_path_buf());
+        }
+    }
+
+    Ok(list)
+}
+
+/// Zip every cached file used by the lockfiles, along with cached metadata
+///
+/// Paths inside the zip are relative to the cache root so `mcman cache import` can extract it as-is
+pub fn export(lockfiles: &[Lockfile], path: &Path) -> Result<usize> {
+    let root = Cache::cache_root().context("Cache directory was missing, maybe it's disabled?")?;
+
+    let mut files = BTreeSet::new();
+
+    for resolved in lockfiles.iter().flat_map(Lockfile::resolved_files) {
+        if let Some(cached) = Cache::locate(resolved).filter(|p| p.exists()) {
+            files.insert(cached);
+        }
+
+        if let CacheStrategy::Indexed { namespace, key } = &resolved.cache {
+            if let Some(index) = Cache::index_path(namespace, key).filter(|p| p.exists()) {
+                files.insert(index);
+            }
+        }
+    }
+
+    let objects_root = root.join(OBJECTS_NAMESPACE);
+    let index_root = root.join(INDEX_NAMESPACE);
+
+    // metadata such as version manifests is needed for offline builds too
+    for file in list_files(&root)? {
+        if !file.path.starts_with(&objects_root)
+            && !file.path.starts_with(&index_root)
+            && file.path.extension().is_some_and(|ext| ext == "json")
+        {
+            files.insert(file.path);
+        }
+    }
+
+    let mut zip =
+        ZipWriter::new(fs::File::create(path).context(format!("Creating {}", path.display()))?);
+
+    for file in &files {
+        let name = file
+            .strip_prefix(&root)?
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+
+        zip.start_file(name, FileOptions::default())?;
+        io::copy(&mut fs::File::open(file)?, &mut zip)
+            .context(format!("Exporting {}", file.display()))?;
+    }
+
+    zip.finish()?;
+
+    Ok(files.len())
+}
+
+fn delete_files(root: &Path, files: &[CacheFile], dry_run: bool) -> Result<()> {
+    let total = files.iter().map(|f| f.size).sum::<u64>();
+
+    for file in files {
+        println!(
+            " {} {} {}",
+            style(if dry_run { "Would delete" } else { "Deleting" }).yellow(),
+            style(file.path.strip_prefix(root)?.to_string_lossy()).dim(),
+            style(format!("({})", HumanBytes(file.size))).dim(),
+        );
+
+        if !dry_run {
+            fs::remove_file(&file.path)
+                .context(format!("Deleting {}", file.path.display()))?;
+        }
+    }
+
+    if !dry_run {
+        // clean up empty folders, ignore errors from non-empty ones
+        for entry in WalkDir::new(root).min_depth(1).contents_first(true) {
+            let entry = entry?;
+            if entry.file_type().is_dir() {
+                let _ = fs::remove_dir(entry.path());
+            }
+        }
+    }
+
+    println!(
+        " {} {} files, {}",
+        if dry_run { "Would free" } else { "Freed" },
+        files.len(),
+        style(HumanBytes(total)).bold()
+    );
+
+    Ok(())
 }
 
 pub fn run(commands: Commands) -> Result<()> {
@@ -90,6 +301,181 @@ pub fn run(commands: Commands) -> Result<()> {
                     pb.finish_and_clear();
                     println!(" Cache has been cleared");
                 }
+
+                Commands::Stats => {
+                    let mut namespaces: BTreeMap<String, (u64, u64)> = BTreeMap::new();
+
+                    for file in list_files(&cache_folder)? {
+                        let (count, size) = namespaces.entry(file.namespace).or_default();
+                        *count += 1;
+                        *size += file.size;
+                    }
+
+                    let mut namespaces = namespaces.into_iter().collect::<Vec<_>>();
+                    namespaces.sort_by_key(|(_, (_, size))| std::cmp::Reverse(*size));
+
+                    let mut table = MarkdownTable::with_headers(vec![
+                        Cow::Borrowed("Namespace"),
+                        Cow::Borrowed("Files"),
+                        Cow::Borrowed("Size"),
+                    ]);
+
+                    for (namespace, (count, size)) in &namespaces {
+                        table.rows.push(vec![
+                            namespace.clone(),
+                            count.to_string(),
+                            HumanBytes(*size).to_string(),
+                        ]);
+                    }
+
+                    println!(
+                        " {}",
+                        style(format!("Folder: {}", cache_folder.to_string_lossy())).dim()
+                    );
+                    println!("{}", table.render_ascii());
+                    println!(
+                        " {} files, {} in total",
+                        namespaces.iter().map(|(_, (c, _))| c).sum::<u64>(),
+                        style(HumanBytes(namespaces.iter().map(|(_, (_, s))| s).sum()))
+                            .bold()
+                    );
+                }
+
+                Commands::Prune {
+                    older_than,
+                    max_size,
+                    dry_run,
+                } => {
+                    if older_than.is_none() && max_size.is_none() {
+                        bail!("Nothing to prune, use --older-than and/or --max-size");
+                    }
+
+                    let now = SystemTime::now();
+                    let index_root = cache_folder.join(INDEX_NAMESPACE);
+
+                    // metadata such as version manifests is needed for offline builds
+                    let (index, files): (Vec<_>, Vec<_>) = deletable_files(&cache_folder)?
+                        .into_iter()
+                        .filter(|f| f.path.starts_with(&index_root) || !is_metadata(f))
+                        .partition(|f| f.path.starts_with(&index_root));
+
+                    // only objects get their modification dates updated when they're used,
+                    // so index entries are deleted along with the object they point to
+                    let mut delete = vec![];
+                    let mut entries: HashMap<PathBuf, Vec<CacheFile>> = HashMap::new();
+                    for file in index {
+                        match index_entry_object(&file.path).filter(|obj| obj.exists()) {
+                            Some(object) => entries.entry(object).or_default().push(file),
+                            // index entries of deleted objects are useless
+                            None => delete.push(file),
+                        }
+                    }
+
+                    let mut files = files
+                        .into_iter()
+                        .map(|f| {
+                            let index = entries.remove(&f.path).unwrap_or_default();
+                            (f, index)
+                        })
+                        .collect::<Vec<_>>();
+                    files.sort_by_key(|(f, _)| f.modified);
+
+                    let size = |(f, index): &(CacheFile, Vec<CacheFile>)| {
+                        f.size + index.iter().map(|e| e.size).sum::<u64>()
+                    };
+
+                    let (mut old, keep): (Vec<_>, Vec<_>) =
+                        files.into_iter().partition(|(f, _)| {
+                            older_than.is_some_and(|d| {
+                                now.duration_since(f.modified).unwrap_or_default() > d
+                            })
+                        });
+
+                    if let Some(max_size) = max_size {
+                        let mut total = keep.iter().map(size).sum::<u64>();
+
+                        // least recently used first
+                        for file in keep {
+                            if total <= max_size {
+                                break;
+                            }
+
+                            total -= size(&file);
+                            old.push(file);
+                        }
+                    }
+
+                    for (file, index) in old {
+                        delete.push(file);
+                        delete.extend(index);
+                    }
+
+                    delete_files(&cache_folder, &delete, dry_run)?;
+                }
+
+                Commands::Gc { paths, dry_run } => {
+                    let paths = if paths.is_empty() {
+                        vec![env::current_dir()?]
+                    } else {
+                        paths
+                    };
+
+                    let mut keep = HashSet::new();
+                    let mut lockfiles = 0;
+
+                    for path in &paths {
+                        for lockfile_path in find_lockfiles(path)? {
+                            let lockfile = Lockfile::load_from(&lockfile_path)
+                                .context(format!("Reading {}", lockfile_path.display()))?;
+
+                            keep.extend(lockfile.resolved_files().filter_map(Cache::locate));
+                            lockfiles += 1;
+                        }
+                    }
+
+                    if lockfiles == 0 {
+                        bail!("No lockfiles found, build the servers first (refusing to delete everything)");
+                    }
+
+                    println!(
+                        " Keeping {} files used by {lockfiles} lockfiles",
+                        keep.len()
+                    );
+
+                    let index_root = cache_folder.join(INDEX_NAMESPACE);
+
+                    let delete = deletable_files(&cache_folder)?
+                        .into_iter()
+                        .filter(|f| {
+                            if f.path.starts_with(&index_root) {
+                                // index entries of deleted objects are useless
+                                index_entry_object(&f.path).map_or(true, |obj| !keep.contains(&obj))
+                            } else {
+                                // metadata such as github releases is kept
+                                !is_metadata(f) && !keep.contains(&f.path)
+                            }
+                        })
+                        .collect::<Vec<_>>();
+
+                    delete_files(&cache_folder, &delete, dry_run)?;
+                }
+
+                Commands::Import { file } => {
+                    let mut archive = ZipArchive::new(
+                        fs::File::open(&file).context(format!("Opening {}", file.display()))?,
+                    )?;
+
+                    fs::create_dir_all(&cache_folder)?;
+                    archive
+                        .extract(&cache_folder)
+                        .context("Extracting into the cache")?;
+
+                    println!(
+                        " Imported {} files into {}",
+                        archive.len(),
+                        style(cache_folder.to_string_lossy()).dim()
+                    );
+                }
             }
 
             Ok(())
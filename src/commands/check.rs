@@ -0,0 +1,300 @@
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display, Formatter},
+    path::Path,
+};
+
+use anyhow::{bail, Context, Result};
+use futures_util::{stream, StreamExt};
+use walkdir::WalkDir;
+
+use crate::{
+    app::{AddonType, App, BaseApp, Resolvable},
+    core::bootstrap::{config_folders, should_bootstrap_file, undefined_variables},
+    model::{Network, Server},
+};
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Check every server of the network instead of the current server
+    #[arg(long)]
+    network: bool,
+    /// Don't resolve addons against the APIs
+    #[arg(long)]
+    offline: bool,
+}
+
+struct Problem {
+    /// File the problem is in, like `server.toml` or `config/server.properties:3`
+    location: String,
+    message: String,
+}
+
+impl Problem {
+    fn new(location: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            location: location.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for Problem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.location, self.message)
+    }
+}
+
+/// Path relative to the network or the server folder
+fn display_path(app: &App, path: &Path) -> String {
+    app.network
+        .as_ref()
+        .and_then(|nw| path.strip_prefix(&nw.path).ok())
+        .or_else(|| path.strip_prefix(&app.server.path).ok())
+        .unwrap_or(path)
+        .display()
+        .to_string()
+}
+
+fn check_network(nw: &Network) -> Vec<Problem> {
+    let mut problems = vec![];
+    let location = "network.toml";
+
+    if !nw.servers.is_empty() && !nw.servers.contains_key(&nw.proxy) {
+        problems.push(Problem::new(
+            location,
+            format!("proxy '{}' isn't in [servers]", nw.proxy),
+        ));
+    }
+
+    for group in nw.proxy_groups.iter().filter(|g| !nw.groups.contains_key(*g)) {
+        problems.push(Problem::new(
+            location,
+            format!("group '{group}' in proxy_groups isn't defined in [groups]"),
+        ));
+    }
+
+    let mut names = nw.servers.keys().collect::<Vec<_>>();
+    names.sort();
+
+    // (ip, port) => servers using it
+    let mut ports: BTreeMap<(&str, u16), Vec<&str>> = BTreeMap::new();
+    let default_ip = "127.0.0.1";
+
+    for name in names {
+        let entry = &nw.servers[name];
+        let ip = entry.ip_address.as_deref().unwrap_or(default_ip);
+
+        for group in entry.groups.iter().filter(|g| !nw.groups.contains_key(*g)) {
+            problems.push(Problem::new(
+                location,
+                format!("group '{group}' of server '{name}' isn't defined in [groups]"),
+            ));
+        }
+
+        if !nw.path.join("servers").join(name).join("server.toml").exists() {
+            problems.push(Problem::new(
+                location,
+                format!("server '{name}' has no servers/{name}/server.toml"),
+            ));
+        }
+
+        // the proxy can listen on the network port itself
+        if *name == nw.proxy {
+            ports.entry((ip, nw.port)).or_default().push(name);
+
+            if entry.port == nw.port {
+                continue;
+            }
+        }
+
+        ports.entry((ip, entry.port)).or_default().push(name);
+    }
+
+    for ((ip, port), servers) in ports.iter().filter(|(_, s)| s.len() > 1) {
+        problems.push(Problem::new(
+            location,
+            format!("{ip}:{port} is used by more than one server: {}", servers.join(", ")),
+        ));
+    }
+
+    problems
+}
+
+fn check_hooks(app: &App) -> Vec<Problem> {
+    let hooks = app
+        .server
+        .hooks
+        .iter()
+        .chain(app.network.iter().flat_map(|nw| nw.hooks.iter()))
+        .filter(|(_, hook)| !hook.disabled);
+
+    let mut problems = vec![];
+    for (name, _) in hooks {
+        let filename = app.hooks().resolve_filename(name);
+        let path = app.server.path.join("hooks").join(&filename);
+
+        if !path.exists() {
+            problems.push(Problem::new(
+                display_path(app, &app.server.path.join("server.toml")),
+                format!("script of hook '{name}' not found at {}", display_path(app, &path)),
+            ));
+        }
+    }
+
+    problems
+}
+
+fn check_variables(app: &App) -> Vec<Problem> {
+    let mut problems = vec![];
+
+    for folder in config_folders(app).into_iter().filter(|f| f.exists()) {
+        for entry in WalkDir::new(&folder).into_iter().filter_map(Result::ok) {
+            let path = entry.path();
+            let rel_path = path.strip_prefix(&folder).unwrap_or(path);
+
+            if !entry.file_type().is_file() || !should_bootstrap_file(app, rel_path) {
+                continue;
+            }
+
+            let content = match std::fs::read_to_string(path) {
+                Ok(content) => content,
+                Err(e) => {
+                    problems.push(Problem::new(display_path(app, path), e.to_string()));
+                    continue;
+                }
+            };
+
+            for (line, key) in undefined_variables(app, &content) {
+                problems.push(Problem::new(
+                    format!("{}:{line}", display_path(app, path)),
+                    format!("variable '{key}' isn't defined"),
+                ));
+            }
+        }
+    }
+
+    problems
+}
+
+/// Resolve the server jar and every addon, world and datapack without downloading
+async fn check_downloads(app: &App) -> Vec<Problem> {
+    let location = display_path(app, &app.server.path.join("server.toml"));
+    let mut problems = vec![];
+
+    if let Err(e) = app.server.jar.resolve_source(app).await {
+        problems.push(Problem::new(
+            &location,
+            format!("server jar {}: {e:#}", app.server.jar),
+        ));
+    }
+
+    let mut downloadables = [AddonType::Plugin, AddonType::Mod]
+        .into_iter()
+        .flat_map(|ty| app.get_addons(ty))
+        .collect::<Vec<_>>();
+
+    for world in app.server.worlds.values() {
+        downloadables.extend(world.download.iter().cloned());
+        downloadables.extend(world.datapacks.iter().cloned());
+    }
+
+    downloadables.extend(app.server.clientsidemods.iter().map(|m| m.dl.clone()));
+
+    app.print_job(&format!("Resolving {} downloadables...", downloadables.len()));
+
+    let results = stream::iter(&downloadables)
+        .map(|dl| async move { (dl, dl.resolve_source(app).await) })
+        .buffered(app.config.downloads.parallel.max(1))
+        .collect::<Vec<_>>()
+        .await;
+
+    for (dl, result) in results {
+        if let Err(e) = result {
+            problems.push(Problem::new(
+                &location,
+                format!("{}: {e:#}", dl.to_short_string()),
+            ));
+        }
+    }
+
+    problems
+}
+
+async fn check_server(app: &App) -> Vec<Problem> {
+    let mut problems = check_hooks(app);
+    problems.extend(check_variables(app));
+
+    if app.config.offline {
+        app.info("Offline, skipped resolving downloads");
+    } else {
+        problems.extend(check_downloads(app).await);
+    }
+
+    problems
+}
+
+pub async fn run(base_app: BaseApp, args: Args) -> Result<()> {
+    let mut app = if args.network {
+        base_app.upgrade_with_default_server()?
+    } else {
+        base_app.upgrade()?
+    };
+
+    if args.offline {
+        app.config.offline = true;
+    }
+
+    let mut problems = app.network.as_ref().map(check_network).unwrap_or_default();
+
+    if args.network {
+        let nw = app
+            .network
+            .as_ref()
+            .context("network.toml not found, --network needs to be run inside a network")?;
+
+        let mut servers = nw
+            .servers
+            .keys()
+            .map(|name| (name.clone(), nw.path.join("servers").join(name)))
+            .collect::<Vec<_>>();
+        servers.sort();
+
+        for (name, dir) in servers {
+            let path = dir.join("server.toml");
+
+            // missing ones are reported by check_network
+            if !path.exists() {
+                continue;
+            }
+
+            app.server = match Server::load_from(&path) {
+                Ok(server) => server,
+                Err(e) => {
+                    problems.push(Problem::new(display_path(&app, &path), format!("{e:#}")));
+                    continue;
+                }
+            };
+
+            app.print_job(&format!("Checking {name}..."));
+            problems.extend(check_server(&app).await);
+        }
+    } else {
+        problems.extend(check_server(&app).await);
+    }
+
+    if problems.is_empty() {
+        app.success("No problems found");
+        return Ok(());
+    }
+
+    for problem in &problems {
+        app.error(problem);
+    }
+
+    bail!(
+        "Found {} problem{}",
+        problems.len(),
+        if problems.len() == 1 { "" } else { "s" }
+    )
+}
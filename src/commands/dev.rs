@@ -13,28 +13,37 @@ use super::run::RunArgs;
 pub struct DevArgs {
     #[command(flatten)]
     run_args: RunArgs,
+    /// Hot reload an already running server over RCON instead of starting one
+    #[arg(long, value_name = "host:port")]
+    attach: Option<String>,
 }
 
-impl DevArgs {
-    pub fn create_dev_session(self, app: &mut App) -> Result<DevSession<'_>> {
-        let config_path = app.server.path.join("hotreload.toml");
+/// Load the server's `hotreload.toml`, creating a default one if it doesn't exist
+pub fn load_hotreload_config(app: &App) -> Result<HotReloadConfig> {
+    let config_path = app.server.path.join("hotreload.toml");
 
-        let config = if config_path.exists() {
-            HotReloadConfig::load_from(&config_path)?
-        } else {
-            app.info("Generated hotreload.toml");
+    Ok(if config_path.exists() {
+        HotReloadConfig::load_from(&config_path)?
+    } else {
+        app.info("Generated hotreload.toml");
 
-            let cfg = HotReloadConfig {
-                path: config_path,
-                ..Default::default()
-            };
-
-            cfg.save()?;
-            cfg
+        let cfg = HotReloadConfig {
+            path: config_path,
+            ..Default::default()
         };
 
+        cfg.save()?;
+        cfg
+    })
+}
+
+impl DevArgs {
+    pub fn create_dev_session(self, app: &mut App) -> Result<DevSession<'_>> {
+        let config = load_hotreload_config(app)?;
+
         let mut dev_session = self.run_args.create_dev_session(app)?;
         dev_session.hot_reload = Some(Arc::new(Mutex::new(config)));
+        dev_session.attach = self.attach;
         // no.
         dev_session.test_mode = false;
 
@@ -0,0 +1,203 @@
+use std::fmt::Write;
+
+use anyhow::{Context, Result};
+
+use crate::{app::BaseApp, model::Server};
+
+use super::docker;
+
+/// Folder next to `network.toml` the Dockerfiles of the services are written to
+const DOCKERFILES_DIR: &str = "docker";
+
+#[derive(clap::Args, Clone)]
+pub struct Args {
+    /// Also map the ports of the backend servers to the host
+    #[arg(long)]
+    expose_all: bool,
+}
+
+struct Service {
+    /// Server name, as in network.toml
+    name: String,
+    /// Compose service name, also used as the hostname
+    service: String,
+    dockerfile: String,
+    worlds: Vec<String>,
+    /// `(host, container)`
+    port: Option<(u16, u16)>,
+    expose: Option<u16>,
+}
+
+/// Compose service names can only contain lowercase letters, digits, `-` and `_`
+fn service_name(name: &str) -> String {
+    name.to_lowercase()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+fn world_names(server: &Server) -> Vec<String> {
+    let mut worlds = server.worlds.keys().cloned().collect::<Vec<_>>();
+    if worlds.is_empty() {
+        worlds.push("world".to_owned());
+    }
+    worlds.sort();
+    worlds
+}
+
+fn generate_compose(services: &[Service], proxy: &str) -> String {
+    let mut yml = String::from("# generated by mcman\nservices:\n");
+    let mut volumes = vec![];
+
+    for s in services {
+        let _ = write!(
+            yml,
+            "  {}:\n    \
+                build:\n      \
+                  context: .\n      \
+                  dockerfile: {DOCKERFILES_DIR}/{}\n    \
+                restart: unless-stopped\n    \
+                stdin_open: true\n    \
+                tty: true\n",
+            s.service, s.dockerfile,
+        );
+
+        if let Some((host, container)) = s.port {
+            let _ = write!(yml, "    ports:\n      - \"{host}:{container}\"\n");
+        }
+
+        if let Some(port) = s.expose {
+            let _ = write!(yml, "    expose:\n      - \"{port}\"\n");
+        }
+
+        if !s.worlds.is_empty() {
+            yml.push_str("    volumes:\n");
+            for world in &s.worlds {
+                let volume = format!("{}-{}", s.service, service_name(world));
+                let _ = writeln!(yml, "      - {volume}:/server/{world}");
+                volumes.push(volume);
+            }
+        }
+
+        if s.name == proxy {
+            let backends = services
+                .iter()
+                .filter(|b| b.name != proxy)
+                .map(|b| format!("      {}:\n        condition: service_healthy\n", b.service))
+                .collect::<String>();
+
+            if !backends.is_empty() {
+                let _ = write!(yml, "    depends_on:\n{backends}");
+            }
+        }
+    }
+
+    if !volumes.is_empty() {
+        yml.push_str("\nvolumes:\n");
+        for volume in volumes {
+            let _ = writeln!(yml, "  {volume}:");
+        }
+    }
+
+    yml
+}
+
+pub async fn run(base_app: BaseApp, args: Args) -> Result<()> {
+    let app = base_app.upgrade_with_default_server()?;
+    let nw = app
+        .network
+        .as_ref()
+        .context("Couldn't find network.toml - `mcman env compose` needs to be run in a network")?;
+
+    let mut names = nw.servers.keys().cloned().collect::<Vec<_>>();
+    if !nw.proxy.is_empty() && !names.contains(&nw.proxy) {
+        names.push(nw.proxy.clone());
+    }
+    names.sort_by_key(|name| (name != &nw.proxy, name.clone()));
+
+    let ip_args = names
+        .iter()
+        .map(|name| format!("ARG IP_{name}={}", service_name(name)))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let dockerfiles = nw.path.join(DOCKERFILES_DIR);
+    std::fs::create_dir_all(&dockerfiles).context("Creating Dockerfile folder")?;
+
+    let mut services = vec![];
+
+    for name in names {
+        let path = nw.path.join("servers").join(&name).join("server.toml");
+        let server = Server::load_from(&path).context(format!("Loading {}", path.display()))?;
+        let mut server_app = app.for_server(server);
+
+        let entry_port = nw.servers.get(&name).map(|e| e.port);
+        let is_proxy = name == nw.proxy;
+
+        let service = Service {
+            service: service_name(&name),
+            dockerfile: format!("{}.Dockerfile", service_name(&name)),
+            worlds: if is_proxy {
+                vec![]
+            } else {
+                world_names(&server_app.server)
+            },
+            port: if is_proxy {
+                Some((nw.port, entry_port.unwrap_or(nw.port)))
+            } else if args.expose_all {
+                entry_port.map(|p| (p, p))
+            } else {
+                None
+            },
+            expose: entry_port.filter(|_| !is_proxy),
+            name,
+        };
+
+        // the healthcheck pings the port inside the container
+        let mut vars = vec![
+            ("SERVER", service.name.clone()),
+            ("IP_ARGS", ip_args.clone()),
+            ("WORLDS", service.worlds.join(" ")),
+        ];
+        if let Some(port) = service.port.map(|(_, port)| port).or(service.expose) {
+            vars.push(("SERVER_PORT", port.to_string()));
+        }
+
+        let dockerfile = docker::render_template(
+            &mut server_app,
+            &docker::Args::default(),
+            include_str!("../../../res/default_dockerfile_network"),
+            &vars,
+        )
+        .await?;
+
+        std::fs::write(dockerfiles.join(&service.dockerfile), dockerfile)
+            .context(format!("Writing {DOCKERFILES_DIR}/{}", service.dockerfile))?;
+
+        services.push(service);
+    }
+
+    std::fs::write(
+        nw.path.join(".dockerignore"),
+        include_str!("../../../res/default_dockerignore_network"),
+    )
+    .context("Writing .dockerignore")?;
+    std::fs::write(
+        nw.path.join("docker-compose.yml"),
+        generate_compose(&services, &nw.proxy),
+    )
+    .context("Writing docker-compose.yml")?;
+
+    app.success(format!(
+        "docker-compose.yml with {} services was written, start it with `docker compose up --build`",
+        services.len()
+    ));
+
+    Ok(())
+}
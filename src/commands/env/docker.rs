@@ -1,15 +1,146 @@
+use std::path::PathBuf;
+
 use anyhow::{Context, Result};
 
 use crate::{
-    app::App,
-    util::env::{write_dockerfile, write_dockerignore},
+    app::{App, Resolvable},
+    core::BuildContext,
+    util::{
+        dollar_repl,
+        env::{write_dockerfile, write_dockerignore},
+    },
 };
 
-pub fn run(app: &App) -> Result<()> {
-    write_dockerfile(&app.server.path).context("writing Dockerfile")?;
+/// Used for proxies and when the required java version can't be found
+pub const DEFAULT_JAVA_VERSION: u32 = 21;
+
+/// Legacy server list ping, the server answers with a kick packet
+///
+/// `nc` comes with busybox on alpine, the other images have bash
+const HEALTHCHECK_ALPINE: &str =
+    "printf '\\376\\001' | nc -w 5 127.0.0.1 ${SERVER_PORT} | grep -q . || exit 1";
+const HEALTHCHECK_BASH: &str = "bash -c 'exec 3<>/dev/tcp/127.0.0.1/${SERVER_PORT} && printf \"\\376\\001\" >&3 && [ -n \"$(head -c 1 <&3)\" ]' || exit 1";
+
+#[derive(clap::Args, Clone, Default)]
+pub struct Args {
+    /// Render this template instead of the default Dockerfile, `${VAR}`s are replaced
+    #[arg(long, value_name = "file")]
+    pub template: Option<PathBuf>,
+    /// Start java directly with the flags of [launcher] instead of start.sh
+    #[arg(long)]
+    pub exec_java: bool,
+    /// Build for multiple platforms with buildx (e.g. linux/amd64,linux/arm64)
+    #[arg(long, value_name = "platforms")]
+    pub platforms: Option<String>,
+}
+
+/// `ENTRYPOINT` that runs java as PID 1, so it gets the stop signal of docker
+async fn exec_java_entrypoint(app: &mut App) -> Result<String> {
+    let ctx = BuildContext::for_built_server(app);
+
+    let jar_name = match ctx.get_built_server_jar_name().await {
+        Ok(name) => name,
+        Err(_) => ctx.app.server.jar.resolve_source(ctx.app).await?.filename,
+    };
+
+    let startup = ctx.get_startup_method(&jar_name).await?;
+
+    let mut command = vec!["java".to_owned()];
+    command.extend(ctx.app.server.launcher.get_arguments(&startup, "linux"));
+
+    Ok(serde_json::to_string(&command)?)
+}
+
+/// The java version the server needs, [`DEFAULT_JAVA_VERSION`] if it can't be found
+pub async fn java_version(app: &App) -> u32 {
+    match app.java().requirement().await {
+        Ok(requirement) => requirement.map_or(DEFAULT_JAVA_VERSION, |r| r.min),
+        Err(e) => {
+            app.warn(format!(
+                "Couldn't find the java version of {}, using {DEFAULT_JAVA_VERSION}: {e}",
+                app.server.name
+            ));
+            DEFAULT_JAVA_VERSION
+        }
+    }
+}
+
+/// Render the Dockerfile template of the server
+pub async fn render_dockerfile(app: &mut App, args: &Args) -> Result<String> {
+    let template = match &args.template {
+        Some(path) => std::fs::read_to_string(path)
+            .context(format!("Reading template {}", path.display()))?,
+        None => include_str!("../../../res/default_dockerfile").to_owned(),
+    };
+
+    render_template(app, args, &template, &[]).await
+}
+
+/// Render a Dockerfile template, `vars` are used before the variables of the server
+pub async fn render_template(
+    app: &mut App,
+    args: &Args,
+    template: &str,
+    vars: &[(&str, String)],
+) -> Result<String> {
+    let java_version = java_version(app).await;
+
+    // alpine images aren't available for every architecture
+    let (java_image, healthcheck) = if args.platforms.is_some() {
+        (format!("eclipse-temurin:{java_version}-jre"), HEALTHCHECK_BASH)
+    } else {
+        (
+            format!("eclipse-temurin:{java_version}-jre-alpine"),
+            HEALTHCHECK_ALPINE,
+        )
+    };
+
+    let entrypoint = if args.exec_java {
+        exec_java_entrypoint(app).await?
+    } else {
+        "[ \"/server/start.sh\" ]".to_owned()
+    };
+
+    let var = |key: &str| {
+        vars.iter()
+            .find(|(name, _)| *name == key)
+            .map(|(_, value)| value.clone())
+            .or_else(|| app.var(key))
+    };
+
+    let port = var("SERVER_PORT").unwrap_or("25565".to_owned());
+
+    let (builder_platform, build_hint) = match &args.platforms {
+        Some(platforms) => (
+            "--platform=$BUILDPLATFORM ".to_owned(),
+            format!("\n# build with: docker buildx build --platform {platforms} ."),
+        ),
+        None => (String::new(), String::new()),
+    };
+
+    let jvm_flags = app.server.launcher.jvm_args.clone();
+
+    Ok(dollar_repl(template, |key| match key {
+        "JAVA_VERSION" => Some(java_version.to_string()),
+        "JAVA_IMAGE" => Some(java_image.clone()),
+        "SERVER_PORT" => Some(port.clone()),
+        "HEALTHCHECK" => Some(healthcheck.replace("${SERVER_PORT}", &port)),
+        "ENTRYPOINT" => Some(entrypoint.clone()),
+        "JVM_FLAGS" => Some(jvm_flags.clone()),
+        "BUILDER_PLATFORM" => Some(builder_platform.clone()),
+        "BUILD_HINT" => Some(build_hint.clone()),
+        "DOCKER_PLATFORMS" => args.platforms.clone(),
+        k => var(k),
+    }))
+}
+
+pub async fn run(mut app: App, args: Args) -> Result<()> {
+    let dockerfile = render_dockerfile(&mut app, &args).await?;
+
+    write_dockerfile(&app.server.path, &dockerfile).context("writing Dockerfile")?;
     write_dockerignore(&app.server.path).context("writing .dockerignore")?;
 
-    app.success("Default docker files were written successfully");
+    app.success("Docker files were written successfully");
 
     Ok(())
 }
@@ -1,29 +1,44 @@
 use anyhow::Result;
 
-use crate::app::App;
+use crate::app::BaseApp;
 
-mod docker;
+mod compose;
+pub mod docker;
 mod gitignore;
+mod systemd;
 mod workflow_packwiz;
 mod workflow_test;
 
-#[derive(clap::Subcommand, Clone, Copy)]
+#[derive(clap::Subcommand, Clone)]
 pub enum Commands {
     /// Modify the gitignore
     Gitignore,
-    /// Write the default Dockerfile and .dockerignore
-    Docker,
+    /// Write a Dockerfile for the server and .dockerignore
+    Docker(docker::Args),
+    /// Write a docker-compose.yml and Dockerfiles for the network
+    Compose(compose::Args),
+    /// Write systemd units for the server or network
+    Systemd(systemd::Args),
     /// github workflow: test the server
     Test,
     /// github workflow: export packwiz automatically
     Packwiz,
 }
 
-pub fn run(app: &App, commands: Commands) -> Result<()> {
+pub async fn run(base_app: BaseApp, commands: Commands) -> Result<()> {
     match commands {
-        Commands::Gitignore => gitignore::run(app),
-        Commands::Docker => docker::run(app),
-        Commands::Packwiz => workflow_packwiz::run(app),
-        Commands::Test => workflow_test::run(app),
+        Commands::Systemd(args) => return systemd::run(base_app, args).await,
+        Commands::Compose(args) => return compose::run(base_app, args).await,
+        Commands::Docker(args) => return docker::run(base_app.upgrade()?, args).await,
+        _ => {}
+    }
+
+    let app = base_app.upgrade()?;
+
+    match commands {
+        Commands::Gitignore => gitignore::run(&app),
+        Commands::Packwiz => workflow_packwiz::run(&app),
+        Commands::Test => workflow_test::run(&app),
+        Commands::Systemd(_) | Commands::Compose(_) | Commands::Docker(_) => unreachable!(),
     }
 }
@@ -0,0 +1,100 @@
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+
+use crate::{
+    app::{App, BaseApp},
+    commands::network::ServerFilter,
+    core::{
+        systemd::{generate_target, unit_name, SystemdOptions},
+        BuildContext,
+    },
+};
+
+#[derive(clap::Args, Clone)]
+pub struct Args {
+    /// The folder to write the units to (default: systemd/)
+    #[arg(short, long, value_name = "dir")]
+    output: Option<PathBuf>,
+    /// The user to run the servers as
+    #[arg(long)]
+    user: Option<String>,
+    /// Generate units for every server of the network and a target to start them together
+    #[arg(long)]
+    network: bool,
+    /// Use the path of the java installation detected on this machine instead of `java`
+    #[arg(long)]
+    detect_java: bool,
+    #[command(flatten)]
+    filter: ServerFilter,
+}
+
+/// Units of an already built server as `(filename, content)`
+async fn server_units(app: &mut App, options: &SystemdOptions) -> Result<Vec<(String, String)>> {
+    let output_dir = app.server.path.join("server");
+    let working_dir = output_dir.canonicalize().context(format!(
+        "{} isn't built yet, build it before generating units",
+        app.server.name
+    ))?;
+
+    let ctx = BuildContext::for_built_server(app);
+
+    let jar_name = ctx.get_built_server_jar_name().await?;
+
+    ctx.generate_systemd_units(&jar_name, &working_dir, options).await
+}
+
+pub async fn run(base_app: BaseApp, args: Args) -> Result<()> {
+    let mut files = vec![];
+
+    let app = if args.network {
+        let app = base_app.upgrade_with_default_server()?;
+        let nw = app.network.clone().context("network.toml not found")?;
+        let target = unit_name(&nw.name);
+
+        let options = SystemdOptions {
+            user: args.user.clone(),
+            target: Some(target.clone()),
+            detect_java: args.detect_java,
+        };
+
+        let mut units = vec![];
+        for server in args.filter.load_servers(&app)? {
+            let mut server_app = app.for_server(server);
+            units.push(unit_name(&server_app.server.name));
+            files.extend(server_units(&mut server_app, &options).await?);
+        }
+
+        files.push((format!("{target}.target"), generate_target(&nw.name, &units)));
+
+        app
+    } else {
+        let mut app = base_app.upgrade()?;
+
+        let options = SystemdOptions {
+            user: args.user.clone(),
+            target: None,
+            detect_java: args.detect_java,
+        };
+
+        files.extend(server_units(&mut app, &options).await?);
+
+        app
+    };
+
+    let output = args.output.unwrap_or(PathBuf::from("systemd"));
+    std::fs::create_dir_all(&output).context("Creating output folder")?;
+
+    for (filename, content) in &files {
+        std::fs::write(output.join(filename), content).context(format!("Writing {filename}"))?;
+        app.log(format!("  {filename}"));
+    }
+
+    app.success(format!(
+        "{} units written to {}, copy them to /etc/systemd/system/ to install",
+        files.len(),
+        output.display()
+    ));
+
+    Ok(())
+}
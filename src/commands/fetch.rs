@@ -0,0 +1,89 @@
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+
+use crate::{
+    app::{App, BaseApp},
+    core::BuildContext,
+    model::{Lockfile, Server},
+};
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Fetch for every server of the network instead of the current server
+    #[arg(long)]
+    network: bool,
+    /// Skip some stages (plugins, mods, worlds)
+    #[arg(short, long, value_name = "stages")]
+    skip: Vec<String>,
+    /// Export the fetched files from the cache to a zip file
+    #[arg(long, value_name = "file")]
+    export: Option<PathBuf>,
+    /// Don't verify hashes of downloaded and cached files
+    #[arg(long)]
+    no_verify: bool,
+}
+
+async fn fetch_server(app: &mut App, skip: &[String]) -> Result<Lockfile> {
+    // nothing of the build output is kept
+    let output_dir = tempfile::tempdir().context("Creating temporary directory")?;
+
+    // reuse the pins of mcman.lock, fetch_all never writes it
+    let mut ctx = BuildContext {
+        skip_stages: skip.to_vec(),
+        ..BuildContext::locked_at(app, output_dir.path().to_path_buf())
+    };
+
+    ctx.fetch_all().await?;
+
+    Ok(ctx.new_lockfile)
+}
+
+pub async fn run(base_app: BaseApp, args: Args) -> Result<()> {
+    let mut app = if args.network {
+        base_app.upgrade_with_default_server()?
+    } else {
+        base_app.upgrade()?
+    };
+
+    if args.no_verify {
+        app.config.downloads.verify = false;
+    }
+
+    let mut lockfiles = vec![];
+
+    if args.network {
+        let nw = app
+            .network
+            .as_ref()
+            .context("network.toml not found, --network needs to be run inside a network")?;
+
+        let mut servers = nw
+            .servers
+            .keys()
+            .map(|name| (name.clone(), nw.path.join("servers").join(name)))
+            .collect::<Vec<_>>();
+        servers.sort();
+
+        for (name, dir) in servers {
+            app.server = Server::load_from(&dir.join("server.toml"))
+                .context(format!("Loading server.toml of {name}"))?;
+
+            lockfiles.push(
+                fetch_server(&mut app, &args.skip)
+                    .await
+                    .context(format!("Fetching {name}"))?,
+            );
+        }
+    } else {
+        lockfiles.push(fetch_server(&mut app, &args.skip).await?);
+    }
+
+    if let Some(path) = args.export {
+        let count = super::cache::export(&lockfiles, &path)?;
+
+        app.success(format!("Exported {count} cached files to {}", path.display()));
+    }
+
+    Ok(())
+}
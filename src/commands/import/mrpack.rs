@@ -1,4 +1,4 @@
-use std::path::Path;
+use std::{collections::BTreeMap, path::Path};
 
 use anyhow::Result;
 use indicatif::ProgressBar;
@@ -27,6 +27,7 @@ pub async fn run(mut app: App, args: Args) -> Result<()> {
                 url: src,
                 filename: None,
                 desc: None,
+                hashes: BTreeMap::new(),
             }
         } else {
             app.dl_from_string(&src).await?
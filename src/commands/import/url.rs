@@ -16,7 +16,8 @@ pub async fn run(mut app: App, args: Args) -> Result<()> {
     let addon = app.dl_from_string(&urlstr).await?;
     let addon_name = addon.to_short_string();
 
-    app.add_addon_inferred(addon)?;
+    let addon_type = app.add_addon_inferred(addon.clone())?;
+    app.add_dependencies_of(addon_type, &addon).await?;
 
     app.save_changes()?;
     app.notify(Prefix::Imported, addon_name);
@@ -14,7 +14,8 @@ use std::{
 use tempfile::Builder;
 
 use crate::{
-    app::BaseApp,
+    app::{App, BaseApp},
+    commands::env::docker::{self, render_dockerfile},
     interop::{mrpack::MRPackReader, packwiz::FileProvider},
     model::{Network, Server, ServerEntry, ServerType, SoftwareType},
     util::{
@@ -262,7 +263,7 @@ pub async fn run(base_app: BaseApp, args: Args) -> Result<()> {
         }
     }
 
-    initialize_environment()?;
+    initialize_environment(&mut app, matches!(ty, InitType::Network)).await?;
 
     if matches!(ty, InitType::Network) {
         println!(
@@ -288,7 +289,7 @@ pub async fn run(base_app: BaseApp, args: Args) -> Result<()> {
     Ok(())
 }
 
-pub fn initialize_environment() -> Result<()> {
+pub async fn initialize_environment(app: &mut App, is_network: bool) -> Result<()> {
     let theme = ColorfulTheme::default();
 
     if write_git().is_err() {
@@ -307,8 +308,17 @@ pub fn initialize_environment() -> Result<()> {
         );
     }
 
-    if get_docker_version().is_ok() {
-        write_dockerfile(Path::new("."))?;
+    if is_network {
+        println!(
+            "{} {}{}{}",
+            theme.prompt_prefix,
+            style("Use '").dim(),
+            style("mcman env compose").bold(),
+            style("' to generate docker files for the network").dim(),
+        );
+    } else if get_docker_version().is_ok() {
+        let dockerfile = render_dockerfile(app, &docker::Args::default()).await?;
+        write_dockerfile(Path::new("."), &dockerfile)?;
         write_dockerignore(Path::new("."))?;
         println!(
             "{} {}",
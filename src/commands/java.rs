@@ -0,0 +1,96 @@
+use std::borrow::Cow;
+
+use anyhow::{Context, Result};
+use console::style;
+
+use crate::{
+    app::BaseApp,
+    interop::java::parse_major,
+    util::md::MarkdownTable,
+};
+
+#[derive(clap::Subcommand)]
+pub enum Commands {
+    /// List detected java installations
+    #[command(visible_alias = "ls")]
+    List,
+    /// Download a Temurin runtime into the cache
+    Install {
+        /// Major java version, defaults to the one the server needs
+        version: Option<String>,
+    },
+    /// Print the java that would be used to run the server
+    Which,
+}
+
+pub async fn run(base_app: BaseApp, commands: Commands) -> Result<()> {
+    match commands {
+        Commands::List => {
+            let app = base_app.upgrade_with_default_server()?;
+            let installations = app.java().detect().await;
+
+            let mut table = MarkdownTable::with_headers(vec![
+                Cow::Borrowed("Major"),
+                Cow::Borrowed("Version"),
+                Cow::Borrowed("Source"),
+                Cow::Borrowed("Path"),
+            ]);
+
+            for java in &installations {
+                table.rows.push(vec![
+                    java.major.to_string(),
+                    java.version.clone(),
+                    java.source.to_string(),
+                    java.path.to_string_lossy().into_owned(),
+                ]);
+            }
+
+            println!("{}", table.render_ascii());
+            println!(" {} installations found", installations.len());
+        }
+        Commands::Install { version } => {
+            let (app, major) = if let Some(version) = version {
+                let major =
+                    parse_major(&version).context(format!("Invalid java version '{version}'"))?;
+                (base_app.upgrade_with_default_server()?, major)
+            } else {
+                let app = base_app.upgrade()?;
+                let major = app
+                    .java()
+                    .required_major()
+                    .await?
+                    .context("The server doesn't need a specific java version, specify one")?;
+                (app, major)
+            };
+
+            let java = app.java().install(major).await?;
+
+            app.success(format!(
+                "Java {} installed at {}",
+                java.version,
+                java.path.display()
+            ));
+        }
+        Commands::Which => {
+            let app = base_app.upgrade()?;
+            let requirement = app.java().requirement().await?;
+            let java = app.java().get_java_for(requirement.as_ref()).await;
+
+            match &requirement {
+                Some(requirement) => app.info(format!(
+                    "{} needs {}",
+                    app.server.name,
+                    style(requirement).bold()
+                )),
+                None => app.info(format!(
+                    "{} doesn't need a specific java version",
+                    app.server.name
+                )),
+            }
+
+            println!("{java}");
+        }
+    }
+
+    Ok(())
+}
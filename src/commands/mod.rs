@@ -1,17 +1,24 @@
 pub mod add;
 pub mod build;
 pub mod cache;
+pub mod check;
 pub mod dev;
 pub mod download;
 pub mod eject;
 pub mod env;
 pub mod export;
+pub mod fetch;
 pub mod import;
 pub mod info;
 pub mod init;
+pub mod java;
 pub mod markdown;
+pub mod network;
+pub mod ping;
 pub mod pull;
+pub mod rcon;
 pub mod run;
+pub mod update;
 pub mod version;
 pub mod world;
 
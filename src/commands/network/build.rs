@@ -0,0 +1,72 @@
+use std::time::Instant;
+
+use anyhow::Result;
+use futures_util::future;
+
+use crate::{app::App, commands::build::BuildOptions};
+
+use super::{ServerFilter, ServerReport};
+
+#[derive(clap::Args)]
+pub struct Args {
+    #[command(flatten)]
+    filter: ServerFilter,
+    /// Skip some stages
+    #[arg(long, value_name = "stages")]
+    skip_stage: Vec<String>,
+    /// Build all servers at the same time
+    #[arg(short, long)]
+    parallel: bool,
+    #[command(flatten)]
+    options: BuildOptions,
+}
+
+async fn build_server(
+    mut app: App,
+    options: BuildOptions,
+    skip_stages: Vec<String>,
+) -> ServerReport {
+    let name = app.server.name.clone();
+    let started = Instant::now();
+
+    let result = async {
+        let output_dir = app.server.path.join("server");
+        let mut ctx = options.create_build_context(&mut app, output_dir, skip_stages)?;
+        ctx.build_all().await?;
+        anyhow::Ok(())
+    }
+    .await;
+
+    ServerReport {
+        name,
+        result,
+        elapsed: started.elapsed(),
+    }
+}
+
+pub async fn run(app: &App, args: Args) -> Result<()> {
+    let builds = args
+        .filter
+        .load_servers(app)?
+        .into_iter()
+        .map(|server| {
+            build_server(
+                app.for_server(server),
+                args.options.clone(),
+                args.skip_stage.clone(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let reports = if args.parallel {
+        future::join_all(builds).await
+    } else {
+        let mut reports = vec![];
+        for build in builds {
+            reports.push(build.await);
+        }
+        reports
+    };
+
+    super::summarize(app, "Build", reports)
+}
@@ -0,0 +1,184 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use anyhow::{bail, Result};
+use console::{style, Color};
+use futures_util::future;
+use tokio::{
+    io::{AsyncBufReadExt, BufReader},
+    sync::mpsc,
+};
+
+use crate::{
+    app::App,
+    commands::{build::BuildOptions, dev::load_hotreload_config},
+    hot_reload::{Command, DevSession},
+};
+
+use super::ServerFilter;
+
+const COLORS: [Color; 6] = [
+    Color::Cyan,
+    Color::Green,
+    Color::Yellow,
+    Color::Magenta,
+    Color::Blue,
+    Color::Red,
+];
+
+#[derive(clap::Args)]
+pub struct Args {
+    #[command(flatten)]
+    filter: ServerFilter,
+    /// Skip some stages
+    #[arg(long, value_name = "stages")]
+    skip_stage: Vec<String>,
+    #[command(flatten)]
+    options: BuildOptions,
+}
+
+/// Read commands from stdin and send them to the selected server
+///
+/// `@name command` sends to a server once, `@name` alone selects it for the next lines
+async fn route_commands(app: &App, servers: &[(String, mpsc::Sender<Command>)]) -> Result<()> {
+    let mut selected = 0;
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+
+    while let Some(line) = lines.next_line().await? {
+        let line = line.trim();
+
+        let (target, command) = if let Some(rest) = line.strip_prefix('@') {
+            let (name, command) = rest.split_once(' ').unwrap_or((rest, ""));
+
+            let Some(idx) = servers.iter().position(|(n, _)| n == name) else {
+                app.warn(format!(
+                    "No server named '{name}', try one of: {}",
+                    servers
+                        .iter()
+                        .map(|(n, _)| n.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ));
+                continue;
+            };
+
+            if command.trim().is_empty() {
+                selected = idx;
+                app.info(format!("Sending commands to {name}"));
+                continue;
+            }
+
+            (idx, command.trim())
+        } else {
+            (selected, line)
+        };
+
+        if command.is_empty() {
+            continue;
+        }
+
+        let (name, tx) = &servers[target];
+        if tx
+            .send(Command::SendCommand(format!("{command}\n")))
+            .await
+            .is_err()
+        {
+            app.warn(format!("{name} isn't running anymore"));
+        }
+    }
+
+    // stdin closed, keep the servers running
+    future::pending().await
+}
+
+pub async fn run(app: &App, args: Args) -> Result<()> {
+    let mut apps = args
+        .filter
+        .load_servers(app)?
+        .into_iter()
+        .map(|server| app.for_server(server))
+        .collect::<Vec<_>>();
+
+    let mut ports = HashMap::new();
+    for app in &apps {
+        let Some(port) = app.var("SERVER_PORT") else {
+            continue;
+        };
+
+        if let Some(other) = ports.insert(port.clone(), app.server.name.clone()) {
+            bail!(
+                "{other} and {} both use port {port}, change one of them in network.toml",
+                app.server.name
+            );
+        }
+    }
+
+    let width = apps
+        .iter()
+        .map(|app| app.server.name.len())
+        .max()
+        .unwrap_or_default();
+
+    let mut senders = vec![];
+    let mut sessions = vec![];
+
+    for (idx, server_app) in apps.iter_mut().enumerate() {
+        let name = server_app.server.name.clone();
+
+        app.info(format!(
+            "{} => {}:{}",
+            style(&name).fg(COLORS[idx % COLORS.len()]).bold(),
+            server_app.var("SERVER_IP").unwrap_or("127.0.0.1".to_owned()),
+            server_app.var("SERVER_PORT").unwrap_or_default(),
+        ));
+
+        let hot_reload = load_hotreload_config(server_app)?;
+        let output_dir = server_app.server.path.join("server");
+
+        let session = DevSession {
+            builder: args.options.clone().create_build_context(
+                server_app,
+                output_dir,
+                args.skip_stage.clone(),
+            )?,
+            jar_name: None,
+            hot_reload: Some(Arc::new(Mutex::new(hot_reload))),
+            test_mode: false,
+            console_prefix: Some(
+                style(format!("{name:width$}"))
+                    .fg(COLORS[idx % COLORS.len()])
+                    .bold()
+                    .to_string(),
+            ),
+            read_stdin: false,
+            junit: None,
+            attach: None,
+        };
+
+        let (tx, rx) = mpsc::channel(32);
+        senders.push((name, tx.clone()));
+        sessions.push(session.start_with(tx, rx));
+    }
+
+    app.info(format!(
+        "Commands go to {}, use {} to send to another server or {} to switch",
+        style(&senders[0].0).bold(),
+        style("@name <command>").bold(),
+        style("@name").bold(),
+    ));
+
+    tokio::select! {
+        results = future::join_all(sessions) => {
+            for result in results {
+                result?;
+            }
+        },
+        result = route_commands(app, &senders) => result?,
+    }
+
+    println!();
+
+    Ok(())
+}
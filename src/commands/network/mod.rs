@@ -0,0 +1,141 @@
+use std::{borrow::Cow, time::Duration};
+
+use anyhow::{bail, Context, Result};
+use console::style;
+use indicatif::FormattedDuration;
+
+use crate::{
+    app::{App, BaseApp},
+    model::Server,
+    util::md::MarkdownTable,
+};
+
+mod build;
+mod dev;
+mod run;
+
+#[derive(clap::Subcommand)]
+pub enum Commands {
+    /// Build every server of the network
+    Build(build::Args),
+    /// Build and test every server of the network, one by one
+    Run(run::Args),
+    /// Start a development session for every server of the network at once
+    Dev(dev::Args),
+}
+
+/// Selects servers of the network
+#[derive(clap::Args)]
+pub struct ServerFilter {
+    /// Only these servers (e.g. lobby,survival)
+    #[arg(long, value_delimiter = ',', value_name = "servers")]
+    only: Vec<String>,
+    /// Skip these servers (e.g. proxy)
+    #[arg(long, value_delimiter = ',', value_name = "servers")]
+    skip: Vec<String>,
+}
+
+impl ServerFilter {
+    /// Load the `server.toml` of every selected server, the proxy comes first
+    ///
+    /// Servers are expected to be in `servers/<name>/` next to `network.toml`
+    pub fn load_servers(&self, app: &App) -> Result<Vec<Server>> {
+        let nw = app
+            .network
+            .as_ref()
+            .context("Couldn't find network.toml - use `mcman init --network` to create one?")?;
+
+        for name in self.only.iter().chain(&self.skip) {
+            if !nw.servers.contains_key(name) {
+                bail!("There is no server named '{name}' in network.toml");
+            }
+        }
+
+        let mut names = nw
+            .servers
+            .keys()
+            .filter(|name| self.only.is_empty() || self.only.contains(name))
+            .filter(|name| !self.skip.contains(name))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        names.sort_by_key(|name| (name != &nw.proxy, name.clone()));
+
+        if names.is_empty() {
+            bail!("No servers selected");
+        }
+
+        names
+            .iter()
+            .map(|name| {
+                let path = nw.path.join("servers").join(name).join("server.toml");
+
+                Server::load_from(&path)
+                    .context(format!("Loading {}", path.display()))
+                    .context(format!("Loading server '{name}'"))
+            })
+            .collect()
+    }
+}
+
+/// Outcome of building/testing one server of the network
+pub struct ServerReport {
+    pub name: String,
+    pub result: Result<()>,
+    pub elapsed: Duration,
+}
+
+/// Print a summary table of all servers, bails if any of them failed
+pub fn summarize(app: &App, action: &str, reports: Vec<ServerReport>) -> Result<()> {
+    let mut table = MarkdownTable::with_headers(vec![
+        Cow::Borrowed("Server"),
+        Cow::Borrowed("Result"),
+        Cow::Borrowed("Time"),
+    ]);
+
+    for report in &reports {
+        table.rows.push(vec![
+            report.name.clone(),
+            if report.result.is_ok() {
+                "ok".to_owned()
+            } else {
+                "failed".to_owned()
+            },
+            FormattedDuration(report.elapsed).to_string(),
+        ]);
+    }
+
+    app.println(table.render_ascii());
+
+    let total = reports.len();
+    let mut failed = vec![];
+
+    for report in reports {
+        if let Err(e) = report.result {
+            app.error(format!("{}: {e:?}", style(&report.name).bold()));
+            failed.push(report.name);
+        }
+    }
+
+    if !failed.is_empty() {
+        bail!(
+            "{action} failed for {}/{total} servers: {}",
+            failed.len(),
+            failed.join(", ")
+        );
+    }
+
+    app.success(format!("{action} succeeded for all {total} servers"));
+
+    Ok(())
+}
+
+pub async fn run(base_app: BaseApp, commands: Commands) -> Result<()> {
+    let app = base_app.upgrade_with_default_server()?;
+
+    match commands {
+        Commands::Build(args) => build::run(&app, args).await,
+        Commands::Run(args) => run::run(&app, args).await,
+        Commands::Dev(args) => dev::run(&app, args).await,
+    }
+}
@@ -0,0 +1,73 @@
+use std::{path::PathBuf, time::Instant};
+
+use anyhow::{bail, Context, Result};
+
+use crate::{
+    app::App,
+    commands::build::BuildOptions,
+    hot_reload::{DevSession, TestResult},
+};
+
+use super::{ServerFilter, ServerReport};
+
+#[derive(clap::Args)]
+pub struct Args {
+    #[command(flatten)]
+    filter: ServerFilter,
+    /// Skip some stages
+    #[arg(long, value_name = "stages")]
+    skip_stage: Vec<String>,
+    /// Write JUnit XML reports to this folder, one file per server
+    #[arg(long, value_name = "folder")]
+    junit: Option<PathBuf>,
+    #[command(flatten)]
+    options: BuildOptions,
+}
+
+pub async fn run(app: &App, args: Args) -> Result<()> {
+    if let Some(dir) = &args.junit {
+        std::fs::create_dir_all(dir).context("Creating JUnit report folder")?;
+    }
+
+    let mut reports = vec![];
+
+    // servers are tested one by one so they don't fight over ports
+    for server in args.filter.load_servers(app)? {
+        let mut app = app.for_server(server);
+        let name = app.server.name.clone();
+        let started = Instant::now();
+
+        let result = async {
+            let output_dir = app.server.path.join("server");
+            let session = DevSession {
+                builder: args.options.clone().create_build_context(
+                    &mut app,
+                    output_dir,
+                    args.skip_stage.clone(),
+                )?,
+                jar_name: None,
+                hot_reload: None,
+                test_mode: true,
+                console_prefix: None,
+                read_stdin: true,
+                junit: args.junit.as_ref().map(|dir| dir.join(format!("{name}.xml"))),
+                attach: None,
+            };
+
+            match session.start().await? {
+                TestResult::Success => anyhow::Ok(()),
+                TestResult::Crashed => bail!("Server crashed"),
+                TestResult::Failed => bail!("Test failed"),
+            }
+        }
+        .await;
+
+        reports.push(ServerReport {
+            name,
+            result,
+            elapsed: started.elapsed(),
+        });
+    }
+
+    super::summarize(app, "Test", reports)
+}
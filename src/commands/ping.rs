@@ -0,0 +1,69 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use console::style;
+
+use crate::{
+    app::BaseApp,
+    protocol::{parse_address, server_address, slp, DEFAULT_PORT},
+};
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Address of the server, defaults to the local server
+    address: Option<String>,
+    /// Print the status JSON as the server sent it
+    #[arg(long)]
+    json: bool,
+    /// Seconds to wait for an answer
+    #[arg(long, default_value_t = 5)]
+    timeout: u64,
+}
+
+pub async fn run(base_app: BaseApp, args: Args) -> Result<()> {
+    let (host, port) = if let Some(address) = &args.address {
+        parse_address(address, DEFAULT_PORT)?
+    } else {
+        let app = base_app.upgrade()?;
+        server_address(&app, &app.server.path.join("server"))
+    };
+
+    let result = slp::ping(&host, port, Duration::from_secs(args.timeout)).await?;
+
+    if args.json {
+        println!("{}", result.raw);
+        return Ok(());
+    }
+
+    let status = &result.status;
+
+    println!(
+        " > {} {}",
+        style(format!("{host}:{port}")).bold(),
+        style(format!("({}ms)", result.latency.as_millis())).dim()
+    );
+
+    for line in status.motd().lines() {
+        println!("   {}", line.trim());
+    }
+
+    println!(
+        "   version: {} {}",
+        style(&status.version.name).bold(),
+        style(format!("(protocol {})", status.version.protocol)).dim()
+    );
+
+    if let Some(players) = &status.players {
+        println!(
+            "   players: {}/{}",
+            style(players.online).bold(),
+            players.max
+        );
+
+        for player in &players.sample {
+            println!("     - {}", player.name);
+        }
+    }
+
+    Ok(())
+}
@@ -7,7 +7,7 @@ use glob::glob;
 use indicatif::ProgressBar;
 use pathdiff::diff_paths;
 
-use crate::app::App;
+use crate::{app::App, model::Lockfile};
 
 #[derive(clap::Args)]
 pub struct Args {
@@ -29,6 +29,11 @@ pub fn run(app: &App, args: Args) -> Result<()> {
     let mut count = 0;
     let mut skipped = 0;
 
+    let lockfile = Lockfile::get_lockfile(&app.server.path.join("server")).unwrap_or_else(|e| {
+        app.warn(format!("Couldn't read server/.mcman.lock: {e:#}"));
+        Lockfile::default()
+    });
+
     for entry in glob(&files)? {
         let entry = entry?;
 
@@ -42,6 +47,26 @@ pub fn run(app: &App, args: Args) -> Result<()> {
         let mut destination = PathBuf::new();
         let mut iter = diff.components();
         iter.next().expect("Path to have atleast 1 component");
+
+        // bootstrapped secrets and values from the environment must stay in server/
+        let rel_path = iter.as_path();
+        if rel_path.extension().is_some_and(|ext| ext == "secret")
+            || lockfile.files.iter().any(|file| {
+                file.path == rel_path
+                    && file
+                        .variables
+                        .iter()
+                        .any(|v| v.starts_with("secret:") || v.starts_with("env:"))
+            })
+        {
+            app.warn(format!(
+                "Skipped {}, it contains secrets or environment values",
+                diff.display()
+            ));
+            skipped += 1;
+            continue;
+        }
+
         destination.push(&app.server.path);
         destination.push("config");
         destination.extend(iter);
@@ -0,0 +1,56 @@
+use std::{env, time::Duration};
+
+use anyhow::{Context, Result};
+
+use crate::{
+    app::BaseApp,
+    protocol::{
+        parse_address, rcon::RconClient, rcon_settings, read_properties, DEFAULT_RCON_PORT,
+    },
+};
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Address of the RCON server, defaults to rcon.port of the local server
+    #[arg(long)]
+    address: Option<String>,
+    /// Defaults to $MCMAN_RCON_PASSWORD or rcon.password of the local server
+    #[arg(long)]
+    password: Option<String>,
+    /// Seconds to wait for the connection
+    #[arg(long, default_value_t = 5)]
+    timeout: u64,
+    /// The command to run
+    #[arg(required = true, trailing_var_arg = true, allow_hyphen_values = true)]
+    command: Vec<String>,
+}
+
+pub async fn run(base_app: BaseApp, args: Args) -> Result<()> {
+    let password = args
+        .password
+        .clone()
+        .or_else(|| env::var("MCMAN_RCON_PASSWORD").ok());
+
+    let (host, port, password) = if let Some(address) = &args.address {
+        let (host, port) = parse_address(address, DEFAULT_RCON_PORT)?;
+        (host, port, password)
+    } else {
+        let app = base_app.upgrade()?;
+        let props = read_properties(&app.server.path.join("server").join("server.properties"))?;
+        let (port, server_password) = rcon_settings(&props)?;
+        ("127.0.0.1".to_owned(), port, password.or(server_password))
+    };
+
+    let password = password.context("No RCON password, use --password or $MCMAN_RCON_PASSWORD")?;
+
+    let mut client =
+        RconClient::connect(&host, port, &password, Duration::from_secs(args.timeout)).await?;
+
+    let output = client.command(&args.command.join(" ")).await?;
+
+    if !output.is_empty() {
+        println!("{output}");
+    }
+
+    Ok(())
+}
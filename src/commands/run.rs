@@ -1,6 +1,11 @@
+use std::path::PathBuf;
+
 use anyhow::Result;
 
-use crate::{app::App, hot_reload::DevSession};
+use crate::{
+    app::App,
+    hot_reload::{DevSession, TestResult},
+};
 
 use super::build::BuildArgs;
 
@@ -11,6 +16,9 @@ pub struct RunArgs {
     /// Test the server (stops it when it ends startup)
     #[arg(long)]
     test: bool,
+    /// Write the test results to a JUnit XML file
+    #[arg(long, value_name = "file", requires = "test")]
+    junit: Option<PathBuf>,
 }
 
 impl RunArgs {
@@ -22,15 +30,23 @@ impl RunArgs {
             jar_name: None,
             hot_reload: None,
             test_mode: self.test,
+            console_prefix: None,
+            read_stdin: true,
+            junit: self.junit,
+            attach: None,
         })
     }
 }
 
 pub async fn run(mut app: App, args: RunArgs) -> Result<()> {
     let dev_session = args.create_dev_session(&mut app)?;
-    dev_session.start().await?;
+    let result = dev_session.start().await?;
 
     println!();
 
+    if result != TestResult::Success {
+        std::process::exit(1);
+    }
+
     Ok(())
 }
@@ -0,0 +1,261 @@
+use std::{borrow::Cow, fmt::Display, time::Duration};
+
+use anyhow::{Context, Result};
+use console::style;
+use indicatif::{ProgressBar, ProgressStyle};
+
+use crate::{
+    app::{App, Prefix, ResolvedFile},
+    model::{Downloadable, Lockfile, COMMITTED_LOCKFILE},
+    util::md::MarkdownTable,
+};
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Only show the available updates, don't write anything
+    #[arg(long)]
+    dry_run: bool,
+    /// Only update addons from these sources (e.g. modrinth,hangar,github)
+    #[arg(long, value_delimiter = ',')]
+    source: Vec<String>,
+    /// Confirm every update one by one
+    #[arg(long, short)]
+    interactive: bool,
+}
+
+#[derive(Debug, Clone)]
+enum Location {
+    Plugin(usize),
+    Mod(usize),
+    Datapack(String, usize),
+    GroupPlugin(String, usize),
+    GroupMod(String, usize),
+}
+
+impl Display for Location {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Plugin(_) => f.write_str("plugins"),
+            Self::Mod(_) => f.write_str("mods"),
+            Self::Datapack(world, _) => write!(f, "worlds.{world}"),
+            Self::GroupPlugin(group, _) => write!(f, "groups.{group}.plugins"),
+            Self::GroupMod(group, _) => write!(f, "groups.{group}.mods"),
+        }
+    }
+}
+
+impl Location {
+    fn get_mut<'a>(&self, app: &'a mut App) -> Option<&'a mut Downloadable> {
+        match self {
+            Self::Plugin(idx) => app.server.plugins.get_mut(*idx),
+            Self::Mod(idx) => app.server.mods.get_mut(*idx),
+            Self::Datapack(world, idx) => app.server.worlds.get_mut(world)?.datapacks.get_mut(*idx),
+            Self::GroupPlugin(group, idx) => app
+                .network
+                .as_mut()?
+                .groups
+                .get_mut(group)?
+                .plugins
+                .get_mut(*idx),
+            Self::GroupMod(group, idx) => app
+                .network
+                .as_mut()?
+                .groups
+                .get_mut(group)?
+                .mods
+                .get_mut(*idx),
+        }
+    }
+}
+
+fn collect_addons(app: &App) -> Vec<(Location, Downloadable)> {
+    let mut list = vec![];
+
+    for (idx, dl) in app.server.plugins.iter().enumerate() {
+        list.push((Location::Plugin(idx), dl.clone()));
+    }
+
+    for (idx, dl) in app.server.mods.iter().enumerate() {
+        list.push((Location::Mod(idx), dl.clone()));
+    }
+
+    let mut worlds = app.server.worlds.iter().collect::<Vec<_>>();
+    worlds.sort_by_key(|(name, _)| *name);
+    for (name, world) in worlds {
+        for (idx, dl) in world.datapacks.iter().enumerate() {
+            list.push((Location::Datapack(name.clone(), idx), dl.clone()));
+        }
+    }
+
+    if let Some(nw) = &app.network {
+        let mut groups = nw.groups.iter().collect::<Vec<_>>();
+        groups.sort_by_key(|(name, _)| *name);
+        for (name, group) in groups {
+            for (idx, dl) in group.plugins.iter().enumerate() {
+                list.push((Location::GroupPlugin(name.clone(), idx), dl.clone()));
+            }
+
+            for (idx, dl) in group.mods.iter().enumerate() {
+                list.push((Location::GroupMod(name.clone(), idx), dl.clone()));
+            }
+        }
+    }
+
+    list
+}
+
+fn matches_source(dl: &Downloadable, sources: &[String]) -> bool {
+    if sources.is_empty() {
+        return true;
+    }
+
+    let ty = dl.get_type_name().to_lowercase();
+
+    sources
+        .iter()
+        .any(|s| ty.starts_with(&s.trim().to_lowercase()))
+}
+
+/// Drop the entries of `mcman.lock` that follow `latest`, so the next build resolves them again
+///
+/// The server jar is only dropped without `--source`. Returns the number of dropped entries
+fn unlock_latest(app: &App, args: &Args) -> Result<usize> {
+    let Some(mut lock) = Lockfile::get_committed(&app.server.path)? else {
+        return Ok(0);
+    };
+
+    let unlock = |entries: &mut Vec<(Downloadable, ResolvedFile)>| {
+        let before = entries.len();
+        entries.retain(|(dl, _)| dl.is_pinned() || !matches_source(dl, &args.source));
+        before - entries.len()
+    };
+
+    let mut count = unlock(&mut lock.plugins) + unlock(&mut lock.mods);
+    count += unlock(&mut lock.world_files);
+    if args.source.is_empty() && lock.server_jar.take().is_some() {
+        count += 1;
+    }
+
+    if count > 0 && !args.dry_run {
+        lock.save()?;
+    }
+
+    Ok(count)
+}
+
+pub async fn run(mut app: App, args: Args) -> Result<()> {
+    let unlocked = unlock_latest(&app, &args)?;
+    if unlocked > 0 && args.dry_run {
+        app.info(format!(
+            "{unlocked} entries of {COMMITTED_LOCKFILE} follow `latest` and would be resolved again"
+        ));
+    } else if unlocked > 0 {
+        app.info(format!(
+            "Dropped {unlocked} entries following `latest` from {COMMITTED_LOCKFILE}, the next build resolves them again"
+        ));
+    }
+
+    let addons = collect_addons(&app)
+        .into_iter()
+        .filter(|(_, dl)| dl.is_pinned() && matches_source(dl, &args.source))
+        .collect::<Vec<_>>();
+
+    if addons.is_empty() {
+        app.info("No pinned addons to update");
+        return Ok(());
+    }
+
+    let pb = app.multi_progress.add(
+        ProgressBar::new(addons.len() as u64)
+            .with_style(ProgressStyle::with_template(
+                "{spinner:.blue} Checking for updates {pos}/{len} {msg}",
+            )?),
+    );
+    pb.enable_steady_tick(Duration::from_millis(250));
+
+    let mut updates = vec![];
+
+    for (location, dl) in addons {
+        pb.set_message(dl.to_short_string());
+
+        let current = dl.get_version().unwrap_or_default().to_owned();
+
+        match dl.fetch_latest_version(&app).await {
+            Ok(Some(latest)) if latest != current => {
+                updates.push((location, dl, current, latest));
+            }
+            Ok(_) => {}
+            Err(e) => app.notify(
+                Prefix::SkippedWarning,
+                format!("{}: {e}", dl.to_short_string()),
+            ),
+        }
+
+        pb.inc(1);
+    }
+
+    pb.finish_and_clear();
+
+    if updates.is_empty() {
+        app.success("Everything is up to date");
+        return Ok(());
+    }
+
+    let mut table = MarkdownTable::with_headers(vec![
+        Cow::Borrowed("Location"),
+        Cow::Borrowed("Addon"),
+        Cow::Borrowed("Current"),
+        Cow::Borrowed("Latest"),
+    ]);
+
+    for (location, dl, current, latest) in &updates {
+        table.rows.push(vec![
+            location.to_string(),
+            dl.to_short_string(),
+            current.clone(),
+            latest.clone(),
+        ]);
+    }
+
+    app.println(table.render_ascii());
+
+    if args.dry_run {
+        app.info(format!(
+            "{} update(s) available, run without {} to apply",
+            updates.len(),
+            style("--dry-run").bold()
+        ));
+        return Ok(());
+    }
+
+    let mut count = 0;
+
+    for (location, dl, current, latest) in updates {
+        if args.interactive
+            && !app.confirm(&format!(
+                "Update {} from {current} to {latest}?",
+                dl.to_short_string()
+            ))?
+        {
+            app.notify(Prefix::Skipped, dl.to_short_string());
+            continue;
+        }
+
+        let entry = location
+            .get_mut(&mut app)
+            .context("Addon entry disappeared while updating")?;
+        *entry = dl.with_version(&latest);
+
+        count += 1;
+    }
+
+    if count == 0 {
+        return Ok(());
+    }
+
+    app.save_changes()?;
+    app.success(format!("Updated {count} addon(s)"));
+    app.refresh_markdown().await?;
+
+    Ok(())
+}
@@ -1,7 +1,8 @@
 use std::{collections::HashSet, io::ErrorKind, time::Duration};
 
 use anyhow::Result;
-use indicatif::{FormattedDuration, ProgressBar, ProgressIterator, ProgressStyle};
+use futures_util::{stream, StreamExt, TryStreamExt};
+use indicatif::{FormattedDuration, ProgressBar, ProgressStyle};
 use tokio::fs;
 
 use crate::app::AddonType;
@@ -51,18 +52,37 @@ impl BuildContext<'_> {
 
         let pb = self.app.multi_progress.add(pb);
 
-        for addon in server_list.iter().progress_with(pb.clone()) {
-            let (_path, resolved) = self
-                .downloadable(addon, addon_type.folder(), Some(&pb))
-                .await?;
+        // resolve and download in parallel, `buffered` keeps the order of server_list
+        let resolved_list = {
+            let this = &*self;
+            let pb = &pb;
+
+            stream::iter(&server_list)
+                .map(|addon| async move {
+                    let (_path, resolved) =
+                        if let Some(locked) = this.get_locked(addon_type, addon) {
+                            this.downloadable(&locked, addon_type.folder(), Some(pb))
+                                .await?
+                        } else {
+                            this.downloadable(addon, addon_type.folder(), Some(pb))
+                                .await?
+                        };
+                    pb.inc(1);
+                    anyhow::Ok((addon.clone(), resolved))
+                })
+                .buffered(this.app.config.downloads.parallel.max(1))
+                .try_collect::<Vec<_>>()
+                .await?
+        };
 
+        for (addon, resolved) in resolved_list {
             files_list.insert(resolved.filename.clone());
 
             match addon_type {
                 AddonType::Plugin => &mut self.new_lockfile.plugins,
                 AddonType::Mod => &mut self.new_lockfile.mods,
             }
-            .push((addon.clone(), resolved));
+            .push((addon, resolved));
         }
 
         pb.set_style(ProgressStyle::with_template(
@@ -3,13 +3,17 @@ use std::{
     time::{Duration, SystemTime},
 };
 
-use anyhow::{anyhow, Context, Result};
+use anyhow::{anyhow, bail, Context, Result};
 use indicatif::{ProgressBar, ProgressStyle};
 use pathdiff::diff_paths;
 use tokio::fs;
 use walkdir::WalkDir;
 
-use crate::{model::BootstrappedFile, util::dollar_repl};
+use crate::{
+    app::App,
+    model::BootstrappedFile,
+    util::{bootstrap_keys, bootstrap_repl},
+};
 
 use super::BuildContext;
 
@@ -35,31 +39,21 @@ impl BuildContext<'_> {
         .map(|e| (e.path.clone(), e.date))
         .collect::<HashMap<_, _>>(); */
 
-        // wtf i have to clone it sorry null
-        if let Some(nw) = self.app.network.clone() {
-            self.bootstrap_folder(nw.path.join("groups").join("global").join("config"))
-                .await?;
-
-            if self.app.server.name == nw.proxy {
-                for group_name in &nw.proxy_groups {
-                    self.bootstrap_folder(nw.path.join("groups").join(group_name).join("config"))
-                        .await?;
-                }
-            }
-
-            if let Some(entry) = nw.servers.get(&self.app.server.name) {
-                for group_name in &entry.groups {
-                    self.bootstrap_folder(nw.path.join("groups").join(group_name).join("config"))
-                        .await?;
-                }
-            }
+        let mut undefined = 0;
+        for folder in config_folders(self.app) {
+            undefined += self.bootstrap_folder(folder).await?;
         }
 
-        self.bootstrap_folder(self.app.server.path.join("config"))
-            .await?;
-
         pb.disable_steady_tick();
         pb.finish_and_clear();
+
+        if undefined > 0 && self.strict {
+            bail!(
+                "{undefined} undefined variable{} in bootstrapped files",
+                if undefined == 1 { "" } else { "s" }
+            );
+        }
+
         self.app.success("Bootstrapping complete");
 
         self.app.ci("::endgroup::");
@@ -67,15 +61,18 @@ impl BuildContext<'_> {
         Ok(())
     }
 
-    pub async fn bootstrap_folder(&mut self, from_path: PathBuf) -> Result<()> {
+    /// Returns the number of undefined variables in the folder
+    pub async fn bootstrap_folder(&mut self, from_path: PathBuf) -> Result<usize> {
         if !from_path.exists() {
             self.app.dbg(format!(
                 "skipped bootstrapping {} because it doesnt exist",
                 from_path.display()
             ));
-            return Ok(());
+            return Ok(0);
         }
 
+        let mut undefined = 0;
+
         for entry in WalkDir::new(&from_path) {
             let entry = entry.map_err(|e| {
                 anyhow!(
@@ -94,60 +91,36 @@ impl BuildContext<'_> {
 
             //pb.set_message(diffed_paths.to_string_lossy().to_string());
 
-            self.bootstrap_file(
-                &source.to_path_buf(),
-                &diffed_paths,
-                None, /* lockfile_entries.get(&diffed_paths) */
-            )
-            .await
-            .context(format!(
-                "Bootstrapping file:
+            undefined += self
+                .bootstrap_file(
+                    &source.to_path_buf(),
+                    &diffed_paths,
+                    None, /* lockfile_entries.get(&diffed_paths) */
+                )
+                .await
+                .context(format!(
+                    "Bootstrapping file:
                 - Entry: {}
                 - Relative: {}",
-                entry.path().display(),
-                diffed_paths.display()
-            ))?;
+                    entry.path().display(),
+                    diffed_paths.display()
+                ))?;
         }
 
-        Ok(())
+        Ok(undefined)
     }
 
     pub fn should_bootstrap_file(&self, path: &Path) -> bool {
-        let ext = path
-            .extension()
-            .unwrap_or_default()
-            .to_str()
-            .unwrap_or_default();
-
-        let bootstrap_exts = [
-            "properties",
-            "txt",
-            "yaml",
-            "yml",
-            "conf",
-            "config",
-            "toml",
-            "json",
-            "json5",
-            "secret",
-        ];
-
-        bootstrap_exts.contains(&ext)
-            || self
-                .app
-                .server
-                .options
-                .bootstrap_exts
-                .iter()
-                .any(|s| s.as_str() == ext)
+        should_bootstrap_file(self.app, path)
     }
 
+    /// Returns the number of undefined variables in the file
     pub async fn bootstrap_file(
         &mut self,
         full_path: &PathBuf,
         rel_path: &PathBuf,
         cache: Option<&SystemTime>,
-    ) -> Result<()> {
+    ) -> Result<usize> {
         let pretty_path = rel_path.display();
 
         let source = full_path;
@@ -163,6 +136,8 @@ impl BuildContext<'_> {
         ))?;
 
         let modified = metadata.modified();
+        let mut variables = vec![];
+        let mut undefined = 0;
 
         if self.force || {
             if let Some(time) = cache {
@@ -192,6 +167,18 @@ impl BuildContext<'_> {
                     ))?
                     .permissions();
 
+                for (line, key) in undefined_variables(self.app, &config_contents) {
+                    self.app.warn(format!("{pretty_path}:{line}: variable '{key}' isn't defined"));
+                    undefined += 1;
+                }
+
+                variables = bootstrap_keys(&config_contents)
+                    .into_iter()
+                    .map(|key| variable_origin(self.app, key))
+                    .collect();
+                variables.sort();
+                variables.dedup();
+
                 let bootstrapped_contents = self.bootstrap_content(&config_contents);
 
                 fs::write(&dest, bootstrapped_contents)
@@ -221,25 +208,123 @@ impl BuildContext<'_> {
             self.new_lockfile.files.push(BootstrappedFile {
                 path: rel_path.clone(),
                 date: source_time,
+                variables,
             });
         } else {
             self.app.warn("File metadata not supported");
         }
 
-        Ok(())
+        Ok(undefined)
     }
 
     pub fn bootstrap_content(&self, content: &str) -> String {
-        dollar_repl(content, |k| {
-            let k = k.trim();
+        bootstrap_repl(content, |k| resolve_variable(self.app, k))
+    }
+}
 
-            let (k, def) = if let Some((k, def)) = k.split_once(':') {
-                (k.trim(), Some(def.trim().to_owned()))
-            } else {
-                (k, None)
-            };
+/// The `config/` folders bootstrapped into the output, later ones overwrite earlier ones
+pub fn config_folders(app: &App) -> Vec<PathBuf> {
+    let mut folders = vec![];
 
-            self.app.var(k).or(def)
-        })
+    if let Some(nw) = &app.network {
+        let groups = nw.path.join("groups");
+
+        folders.push(groups.join("global").join("config"));
+
+        if app.server.name == nw.proxy {
+            for group_name in &nw.proxy_groups {
+                folders.push(groups.join(group_name).join("config"));
+            }
+        }
+
+        if let Some(entry) = nw.servers.get(&app.server.name) {
+            for group_name in &entry.groups {
+                folders.push(groups.join(group_name).join("config"));
+            }
+        }
+    }
+
+    folders.push(app.server.path.join("config"));
+
+    folders
+}
+
+pub fn should_bootstrap_file(app: &App, path: &Path) -> bool {
+    let ext = path
+        .extension()
+        .unwrap_or_default()
+        .to_str()
+        .unwrap_or_default();
+
+    let bootstrap_exts = [
+        "properties",
+        "txt",
+        "yaml",
+        "yml",
+        "conf",
+        "config",
+        "toml",
+        "json",
+        "json5",
+        "secret",
+    ];
+
+    bootstrap_exts.contains(&ext)
+        || app
+            .server
+            .options
+            .bootstrap_exts
+            .iter()
+            .any(|s| s.as_str() == ext)
+}
+
+/// Name of the variable in `${key}`, without the default value
+fn variable_name(key: &str) -> &str {
+    key.split_once(":-").map_or(key, |(key, _)| key).trim()
+}
+
+/// Name of the variable in `${key}` as it is stored in the lockfile, prefixed
+/// with `env:` if its value came from the environment or a `.env` file
+fn variable_origin(app: &App, key: &str) -> String {
+    let name = variable_name(key);
+
+    if !name.starts_with("secret:")
+        && app.builtin_var(name).is_none()
+        && app.env_var(name).is_some()
+    {
+        format!("env:{name}")
+    } else {
+        name.to_owned()
+    }
+}
+
+/// Value of a `${key}`, `${key:-default}` or `${secret:NAME}` in a bootstrapped file
+pub fn resolve_variable(app: &App, key: &str) -> Option<String> {
+    let key = key.trim();
+
+    if let Some(name) = key.strip_prefix("secret:") {
+        return app.secret(name);
     }
+
+    let (key, def) = if let Some((key, def)) = key.split_once(":-") {
+        (key.trim(), Some(def.trim().to_owned()))
+    } else {
+        (key, None)
+    };
+
+    app.var(key).or(def)
+}
+
+/// Keys that can't be resolved, with the line they are on (starting from 1)
+pub fn undefined_variables(app: &App, content: &str) -> Vec<(usize, String)> {
+    content
+        .lines()
+        .enumerate()
+        .flat_map(|(i, line)| {
+            bootstrap_keys(line)
+                .into_iter()
+                .filter(|key| resolve_variable(app, key).is_none())
+                .map(move |key| (i + 1, variable_name(key).to_owned()))
+        })
+        .collect()
 }
@@ -0,0 +1,76 @@
+use std::collections::BTreeMap;
+
+use anyhow::{bail, Result};
+use console::style;
+use futures_util::{stream, StreamExt};
+
+use crate::{app::AddonType, model::Dependency};
+
+use super::BuildContext;
+
+impl BuildContext<'_> {
+    /// Warn about required dependencies of addons that aren't in the addon list,
+    /// bail instead if `strict`
+    pub async fn check_dependencies(&self) -> Result<()> {
+        let addons = [AddonType::Plugin, AddonType::Mod]
+            .into_iter()
+            .flat_map(|ty| self.app.get_addons(ty))
+            .collect::<Vec<_>>();
+
+        self.app.print_job("Checking dependencies...");
+
+        let results = stream::iter(&addons)
+            .map(|addon| async move { (addon, addon.required_dependencies(self.app).await) })
+            .buffered(self.app.config.downloads.parallel.max(1))
+            .collect::<Vec<_>>()
+            .await;
+
+        // dependency name => (dependency, addons that need it)
+        let mut missing: BTreeMap<String, (Dependency, Vec<String>)> = BTreeMap::new();
+
+        for (addon, result) in results {
+            match result {
+                Ok(deps) => {
+                    for dep in deps {
+                        if !dep.is_satisfied_by(&addons) {
+                            missing
+                                .entry(dep.name.clone())
+                                .or_insert((dep, vec![]))
+                                .1
+                                .push(addon.to_short_string());
+                        }
+                    }
+                }
+                Err(e) => self.app.warn(format!(
+                    "Couldn't check the dependencies of {}: {e}",
+                    addon.to_short_string()
+                )),
+            }
+        }
+
+        if missing.is_empty() {
+            return Ok(());
+        }
+
+        for (dep, needed_by) in missing.values() {
+            self.app.warn(format!(
+                "{} is missing, it's required by {}",
+                style(&dep.name).bold(),
+                needed_by.join(", ")
+            ));
+
+            if let Some(code) = dep.to_shortcode() {
+                self.app.log(format!("  add it with `mcman import url {code}`"));
+            }
+        }
+
+        if self.strict {
+            bail!(
+                "{} required dependencies are missing, add them or skip this check with `--skip dependencies`",
+                missing.len()
+            );
+        }
+
+        Ok(())
+    }
+}
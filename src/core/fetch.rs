@@ -0,0 +1,130 @@
+use anyhow::{bail, Context, Result};
+use console::style;
+
+use crate::{
+    app::{AddonType, Cache},
+    model::{InstallMethod, Lockfile},
+};
+
+use super::BuildContext;
+
+impl BuildContext<'_> {
+    /// Resolve and download everything the server needs into the cache, without building it
+    ///
+    /// `output_dir` is expected to be a throwaway folder. Resolutions are written to the
+    /// `.mcman.lock` of the server's default output folder so `--offline` builds can find
+    /// them later, the committed lockfile is only read.
+    pub async fn fetch_all(&mut self) -> Result<()> {
+        let server_name = self.app.server.name.clone();
+        self.app.print_job(&format!(
+            "{} {}...",
+            style("Fetching").bold(),
+            style(&server_name).green().bold()
+        ));
+
+        self.reload();
+
+        self.app.ci("::group::Server Jar");
+        self.fetch_server_jar().await?;
+        self.check_installer().await?;
+        self.app.ci("::endgroup::");
+
+        if self.skip_stages.iter().all(|s| s.as_str() != "plugins") {
+            self.download_addons(AddonType::Plugin).await?;
+        }
+
+        if self.skip_stages.iter().all(|s| s.as_str() != "mods") {
+            self.download_addons(AddonType::Mod).await?;
+        }
+
+        if self.skip_stages.iter().all(|s| s.as_str() != "worlds") {
+            self.fetch_world_files().await?;
+        }
+
+        for resolved in self.new_lockfile.resolved_files() {
+            if Cache::locate(resolved).map_or(true, |path| !path.exists()) {
+                self.app.warn(format!(
+                    "{} can't be cached, offline builds will fail",
+                    resolved.filename
+                ));
+            }
+        }
+
+        self.write_fetched_lockfile()?;
+
+        self.app.success(format!(
+            "Fetched everything for {}",
+            style(&server_name).green().bold()
+        ));
+
+        Ok(())
+    }
+
+    /// Installers download libraries while running, so their output can't be
+    /// cached. Bail unless the server was already installed by a build
+    async fn check_installer(&self) -> Result<()> {
+        let InstallMethod::Installer { name, jar_name, .. } = self.get_install_method().await?
+        else {
+            return Ok(());
+        };
+
+        let jar_name = jar_name.replace("${mcver}", &self.app.server.mc_version);
+        if self.app.server.path.join("server").join(jar_name).exists() {
+            return Ok(());
+        }
+
+        bail!(
+            "{name} has to run with internet access and can't be fetched, \
+            run `mcman build` once so `--offline` builds can reuse the installed server"
+        );
+    }
+
+    /// Record the resolutions in `server/.mcman.lock`, keeping what the last build wrote
+    fn write_fetched_lockfile(&self) -> Result<()> {
+        let mut lock = Lockfile::get_lockfile(&self.app.server.path.join("server"))?;
+        let skipped = |stage: &str| self.skip_stages.iter().any(|s| s.as_str() == stage);
+
+        lock.server_jar.clone_from(&self.new_lockfile.server_jar);
+        if !skipped("plugins") {
+            lock.plugins.clone_from(&self.new_lockfile.plugins);
+        }
+        if !skipped("mods") {
+            lock.mods.clone_from(&self.new_lockfile.mods);
+        }
+        if !skipped("worlds") {
+            lock.world_files.clone_from(&self.new_lockfile.world_files);
+        }
+
+        if let Some(parent) = lock.path.parent() {
+            std::fs::create_dir_all(parent).context("Creating output directory")?;
+        }
+        lock.save()?;
+        self.app.log("updated lockfile");
+
+        Ok(())
+    }
+
+    /// Download world downloads and datapacks without unpacking them
+    async fn fetch_world_files(&mut self) -> Result<()> {
+        let mut world_files = vec![];
+
+        for (name, world) in &self.app.server.worlds {
+            let downloads = world.download.iter().chain(&world.datapacks);
+
+            for dl in downloads {
+                let (_, resolved) = if let Some(locked) = self.get_locked_world_file(dl) {
+                    self.downloadable(&locked, ".mcman-cache", None).await
+                } else {
+                    self.downloadable(dl, ".mcman-cache", None).await
+                }
+                .context(format!("Fetching world files of {name}"))?;
+
+                world_files.push((dl.clone(), resolved));
+            }
+        }
+
+        self.new_lockfile.world_files.extend(world_files);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,122 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+
+use crate::{
+    app::AddonType,
+    interop::jar::{read_jar_metadata, JarMetadata},
+};
+
+use super::BuildContext;
+
+impl BuildContext<'_> {
+    /// Read the descriptors of downloaded plugins and mods and warn about
+    /// ones that won't load on this server
+    pub fn inspect_addons(&self) -> Result<()> {
+        let addon_lists = [
+            (AddonType::Plugin, &self.new_lockfile.plugins),
+            (AddonType::Mod, &self.new_lockfile.mods),
+        ];
+
+        if addon_lists.iter().all(|(_, list)| list.is_empty()) {
+            return Ok(());
+        }
+
+        self.app.print_job("Inspecting jars...");
+
+        let server = &self.app.server.jar;
+        let software = server
+            .get_modrinth_name()
+            .map_or_else(|| server.to_string(), ToOwned::to_owned);
+
+        for (addon_type, list) in addon_lists {
+            let mut inspected: Vec<(String, JarMetadata)> = vec![];
+
+            for (addon, resolved) in list {
+                let path = self
+                    .output_dir
+                    .join(addon_type.folder())
+                    .join(&resolved.filename);
+
+                let metadata = match read_jar_metadata(&path) {
+                    Ok(metadata) => metadata,
+                    Err(e) => {
+                        self.app
+                            .warn(format!("Couldn't inspect {}: {e:#}", resolved.filename));
+                        continue;
+                    }
+                };
+
+                // no descriptor, probably a library
+                let Some(first) = metadata.first().map(|m| m.platform) else {
+                    continue;
+                };
+
+                let addon_name = addon.to_short_string();
+
+                let Some(meta) = metadata.into_iter().find(|m| m.platform.runs_on(server)) else {
+                    self.app
+                        .warn(format!("{addon_name} is a {first}, it can't run on {software}"));
+                    continue;
+                };
+
+                if let Some(range) = &meta.minecraft {
+                    if range.matches(self.app.mc_version()) == Some(false) {
+                        self.app.warn(format!(
+                            "{addon_name} needs Minecraft {range}, but mc_version is {}",
+                            self.app.mc_version()
+                        ));
+                    }
+                }
+
+                if let (Some(range), Some(loader)) =
+                    (&meta.loader, meta.platform.loader_version(server))
+                {
+                    if range.matches(loader) == Some(false) {
+                        self.app.warn(format!(
+                            "{addon_name} needs loader version {range}, but the server is {server}"
+                        ));
+                    }
+                }
+
+                inspected.push((addon_name, meta));
+            }
+
+            let is_installed = |name: &str| {
+                inspected.iter().any(|(_, m)| {
+                    m.name.eq_ignore_ascii_case(name)
+                        || m.provides.iter().any(|p| p.eq_ignore_ascii_case(name))
+                })
+            };
+
+            for (addon_name, meta) in &inspected {
+                for dep in meta.depends.iter().filter(|dep| !is_installed(dep)) {
+                    self.app
+                        .warn(format!("{addon_name} depends on {dep}, which isn't installed"));
+                }
+            }
+
+            // lowercase name => (name, addons declaring it)
+            let mut names: BTreeMap<String, (&str, Vec<&str>)> = BTreeMap::new();
+            for (addon_name, meta) in &inspected {
+                let (_, addons) = names
+                    .entry(meta.name.to_lowercase())
+                    .or_insert((meta.name.as_str(), vec![]));
+
+                // the same addon can be listed twice in a network
+                if !addons.contains(&addon_name.as_str()) {
+                    addons.push(addon_name);
+                }
+            }
+
+            for (name, addons) in names.values().filter(|(_, addons)| addons.len() > 1) {
+                self.app.warn(format!(
+                    "{name} is installed by more than one {addon_type}: {}",
+                    addons.join(", ")
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
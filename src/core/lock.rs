@@ -0,0 +1,256 @@
+use anyhow::{bail, Context, Result};
+
+use crate::{
+    app::{AddonType, ResolvedFile},
+    model::{Downloadable, Lockfile, ServerType, COMMITTED_LOCKFILE},
+    sources::quilt,
+};
+
+use super::BuildContext;
+
+type Entry = (Downloadable, ResolvedFile);
+
+impl BuildContext<'_> {
+    /// Lockfiles resolutions can be reused from, if they were made for this server jar/version
+    ///
+    /// The committed lockfile whenever there is one, and the one in the output directory
+    /// with `--offline`
+    fn usable_locks(&self) -> impl Iterator<Item = &Lockfile> {
+        let committed = self.committed_lockfile.as_ref();
+        let output = Some(&self.lockfile).filter(|_| self.app.config.offline);
+
+        committed
+            .into_iter()
+            .chain(output)
+            .filter(|lock| lock.matches_server(&self.app.server))
+    }
+
+    /// Recorded resolution of an addon from a usable lockfile
+    pub fn get_locked(&self, addon_type: AddonType, dl: &Downloadable) -> Option<ResolvedFile> {
+        self.usable_locks()
+            .find_map(|lock| lock.find_addon(addon_type, dl))
+            .cloned()
+    }
+
+    /// Recorded resolution of a world download or datapack from a usable lockfile
+    pub fn get_locked_world_file(&self, dl: &Downloadable) -> Option<ResolvedFile> {
+        self.usable_locks()
+            .find_map(|lock| lock.find_world_file(dl))
+            .cloned()
+    }
+
+    /// Recorded resolution of the server jar (or its installer) from a usable lockfile
+    pub fn get_locked_server_jar(&self) -> Option<ResolvedFile> {
+        self.usable_locks()
+            .find_map(|lock| lock.server_jar.as_ref())
+            .map(|jar| jar.resolved.clone())
+    }
+
+    fn get_locked_loader_version(&self) -> Option<String> {
+        self.new_lockfile
+            .server_jar
+            .iter()
+            .chain(self.usable_locks().filter_map(|lock| lock.server_jar.as_ref()))
+            .find_map(|jar| jar.loader_version.clone())
+    }
+
+    /// Resolve `latest` loader versions of quilt/forge/neoforge, preferring lockfiles
+    ///
+    /// Returns `None` for server jars without a separate loader
+    pub async fn resolve_loader_version(&self) -> Result<Option<String>> {
+        let loader = match &self.app.server.jar {
+            ServerType::Quilt { loader, .. }
+            | ServerType::NeoForge { loader }
+            | ServerType::Forge { loader } => loader,
+            _ => return Ok(None),
+        };
+
+        if let Some(version) = self.get_locked_loader_version() {
+            return Ok(Some(version));
+        }
+
+        if self.app.config.offline {
+            bail!("Loader version '{loader}' isn't in a lockfile, can't resolve it in offline mode");
+        }
+
+        Ok(Some(match &self.app.server.jar {
+            ServerType::Quilt { .. } => {
+                quilt::map_quilt_loader_version(&self.app.http_client, loader)
+                    .await
+                    .context("resolving quilt loader version id (latest/latest-beta)")?
+            }
+            ServerType::NeoForge { .. } => self.app.neoforge().resolve_version(loader).await?,
+            _ => self.app.forge().resolve_version(loader).await?,
+        }))
+    }
+
+    /// Bail with everything that can't be built without network access
+    pub fn check_offline(&self) -> Result<()> {
+        let skipped = |stage: &str| self.skip_stages.iter().any(|s| s.as_str() == stage);
+        let mut problems = vec![];
+
+        if self.get_locked_server_jar().is_none() {
+            problems.push("server jar".to_owned());
+        }
+
+        if matches!(
+            self.app.server.jar,
+            ServerType::Quilt { .. } | ServerType::NeoForge { .. } | ServerType::Forge { .. }
+        ) && self.get_locked_loader_version().is_none()
+        {
+            problems.push("loader version".to_owned());
+        }
+
+        for (addon_type, stage) in [(AddonType::Plugin, "plugins"), (AddonType::Mod, "mods")] {
+            if skipped(stage) {
+                continue;
+            }
+
+            for dl in self.app.get_addons(addon_type) {
+                if self.get_locked(addon_type, &dl).is_none() {
+                    problems.push(format!("{addon_type} {dl}"));
+                }
+            }
+        }
+
+        if !skipped("worlds") {
+            for (name, world) in &self.app.server.worlds {
+                if let Some(dl) = &world.download {
+                    let present = self.world_exists_in_output(name).unwrap_or(false)
+                        || self.world_source_exists(name);
+
+                    if !present && self.get_locked_world_file(dl).is_none() {
+                        problems.push(format!("world {name}: {dl}"));
+                    }
+                }
+
+                for dp in &world.datapacks {
+                    if self.get_locked_world_file(dp).is_none() {
+                        problems.push(format!("world {name}: datapack {dp}"));
+                    }
+                }
+            }
+        }
+
+        if !problems.is_empty() {
+            bail!(
+                "Can't build offline, these aren't in {COMMITTED_LOCKFILE} or .mcman.lock:\n{}\nRun a build with internet access first",
+                problems
+                    .iter()
+                    .map(|p| format!("  - {p}"))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Bail if server.toml (or network.toml) doesn't match the committed lockfile anymore
+    pub fn check_frozen(&self) -> Result<()> {
+        let Some(lock) = &self.committed_lockfile else {
+            bail!("--frozen requires a {COMMITTED_LOCKFILE} next to server.toml, create one using `mcman build --locked`");
+        };
+
+        let mut problems = vec![];
+
+        if !lock.matches_server(&self.app.server) {
+            problems.push("mc_version or server jar changed".to_owned());
+        }
+
+        for addon_type in [AddonType::Plugin, AddonType::Mod] {
+            let current = self.app.get_addons(addon_type);
+            let locked = lock.addons(addon_type);
+
+            for dl in &current {
+                if !locked.iter().any(|(l, _)| l == dl) {
+                    problems.push(format!("{addon_type} {dl} is not in the lockfile"));
+                }
+            }
+
+            for (dl, _) in locked {
+                if !current.contains(dl) {
+                    problems.push(format!("{addon_type} {dl} was removed"));
+                }
+            }
+        }
+
+        let world_files = self
+            .app
+            .server
+            .worlds
+            .values()
+            .flat_map(|world| world.download.iter().chain(&world.datapacks))
+            .collect::<Vec<_>>();
+
+        for dl in &world_files {
+            if lock.find_world_file(dl).is_none() {
+                problems.push(format!("world file {dl} is not in the lockfile"));
+            }
+        }
+
+        for (dl, _) in &lock.world_files {
+            if !world_files.contains(&dl) {
+                problems.push(format!("world file {dl} was removed"));
+            }
+        }
+
+        if !problems.is_empty() {
+            bail!(
+                "server.toml changed since {COMMITTED_LOCKFILE} was written:\n{}",
+                problems
+                    .iter()
+                    .map(|p| format!("  - {p}"))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Write the resolutions of this build to the committed lockfile, never with `--frozen`
+    /// or `--offline`
+    ///
+    /// Only updates an existing lockfile, `--locked` creates it if it's missing. Builds reuse
+    /// its pins, so only new addons are added and removed ones dropped
+    pub fn write_committed_lockfile(&self) -> Result<()> {
+        if self.frozen
+            || self.app.config.offline
+            || (self.committed_lockfile.is_none() && !self.locked)
+        {
+            return Ok(());
+        }
+
+        let old = self.committed_lockfile.clone().unwrap_or_default();
+
+        let skipped = |stage: &str| self.skip_stages.iter().any(|s| s.as_str() == stage);
+        let entries = |stage: &str, old: &[Entry], new: &[Entry]| {
+            if skipped(stage) {
+                old.to_vec()
+            } else {
+                new.to_vec()
+            }
+        };
+
+        let lock = Lockfile {
+            path: self.app.server.path.join(COMMITTED_LOCKFILE),
+            server_jar: self.new_lockfile.server_jar.clone(),
+            plugins: entries("plugins", &old.plugins, &self.new_lockfile.plugins),
+            mods: entries("mods", &old.mods, &self.new_lockfile.mods),
+            world_files: entries("worlds", &old.world_files, &self.new_lockfile.world_files),
+            ..Default::default()
+        };
+
+        if self.committed_lockfile.is_some()
+            && serde_json::to_value(&lock)? == serde_json::to_value(&old)?
+        {
+            return Ok(());
+        }
+
+        lock.save()?;
+        self.app.log(format!("updated {COMMITTED_LOCKFILE}"));
+
+        Ok(())
+    }
+}
@@ -12,8 +12,13 @@ use crate::{
 
 pub mod addons;
 pub mod bootstrap;
+pub mod dependencies;
+pub mod fetch;
+pub mod inspect;
+pub mod lock;
 pub mod scripts;
 pub mod serverjar;
+pub mod systemd;
 pub mod worlds;
 
 #[derive(Debug)]
@@ -24,12 +29,48 @@ pub struct BuildContext<'a> {
     pub lockfile: Lockfile,
     pub new_lockfile: Lockfile,
 
+    /// Lockfile next to server.toml, see `core/lock.rs`
+    pub committed_lockfile: Option<Lockfile>,
+
     pub force: bool,
+    /// Create `committed_lockfile` if it doesn't exist yet
+    pub locked: bool,
+    /// Like `locked`, but bail if server.toml changed
+    pub frozen: bool,
+    /// Fail when required dependencies of addons are missing or variables are undefined
+    pub strict: bool,
     pub skip_stages: Vec<String>,
     // TODO: Use later?
     pub _server_process: Option<Child>,
 }
 
+impl<'a> BuildContext<'a> {
+    /// A context that reuses the pins of `mcman.lock` and builds into `output_dir`
+    pub fn locked_at(app: &'a mut App, output_dir: PathBuf) -> Self {
+        Self {
+            app,
+            force: false,
+            locked: true,
+            frozen: false,
+            strict: false,
+            skip_stages: vec![],
+            output_dir,
+            lockfile: Lockfile::default(),
+            new_lockfile: Lockfile::default(),
+            committed_lockfile: None,
+            _server_process: None,
+        }
+    }
+
+    /// A context for the already built server in `server/`, with its lockfiles loaded
+    pub fn for_built_server(app: &'a mut App) -> Self {
+        let output_dir = app.server.path.join("server");
+        let mut ctx = Self::locked_at(app, output_dir);
+        ctx.reload();
+        ctx
+    }
+}
+
 impl BuildContext<'_> {
     pub async fn build_all(&mut self) -> Result<String> {
         let server_name = self.app.server.name.clone();
@@ -52,6 +93,14 @@ impl BuildContext<'_> {
 
         self.reload();
 
+        if self.frozen {
+            self.check_frozen()?;
+        }
+
+        if self.app.config.offline {
+            self.check_offline()?;
+        }
+
         if !self.skip_stages.is_empty() {
             self.app
                 .info(format!("Skipping stages: {}", self.skip_stages.join(", ")));
@@ -77,6 +126,16 @@ impl BuildContext<'_> {
             self.download_addons(AddonType::Mod).await?;
         }
 
+        if !self.app.config.offline
+            && self.skip_stages.iter().all(|s| s.as_str() != "dependencies")
+        {
+            self.check_dependencies().await?;
+        }
+
+        if self.skip_stages.iter().all(|s| s.as_str() != "inspect") {
+            self.inspect_addons()?;
+        }
+
         if !self.app.server.worlds.is_empty()
             && self.skip_stages.iter().all(|s| s.as_str() != "worlds")
         {
@@ -95,6 +154,12 @@ impl BuildContext<'_> {
             self.create_scripts(startup).await?;
 
             self.app.log("start.bat and start.sh created");
+
+            if self.app.server.launcher.systemd {
+                self.create_systemd_units(&server_jar).await?;
+
+                self.app.log("systemd units created");
+            }
         }
 
         if self.app.server.launcher.eula_args && !self.app.server.jar.supports_eula_args() {
@@ -141,6 +206,14 @@ impl BuildContext<'_> {
             path: self.output_dir.join(".mcman.lock"),
             ..Default::default()
         };
+
+        self.committed_lockfile = match Lockfile::get_committed(&self.app.server.path) {
+            Ok(lock) => lock,
+            Err(e) => {
+                self.app.warn(format!("Couldn't read committed lockfile: {e}"));
+                None
+            }
+        };
     }
 
     /// Save `new_lockfile`
@@ -152,6 +225,8 @@ impl BuildContext<'_> {
             self.app.log("updated lockfile");
         }
 
+        self.write_committed_lockfile()?;
+
         Ok(())
     }
 
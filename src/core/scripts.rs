@@ -12,9 +12,9 @@ impl BuildContext<'_> {
     pub async fn get_startup_method(&self, serverjar_name: &str) -> Result<StartupMethod> {
         let mcver = self.app.mc_version();
         Ok(match &self.app.server.jar {
-            ServerType::NeoForge { loader } => {
+            ServerType::NeoForge { .. } => {
                 use crate::sources::neoforge::NEOFORGE_BREAKOFF_VERSION;
-                let loader_ver = self.app.neoforge().resolve_version(loader).await?;
+                let loader_ver = self.resolve_loader_version().await?.unwrap_or_default();
 
                 if Version::parse(mcver)? <= NEOFORGE_BREAKOFF_VERSION {
                     StartupMethod::Custom {
@@ -36,8 +36,8 @@ impl BuildContext<'_> {
                     }
                 }
             }
-            ServerType::Forge { loader } => {
-                let l = self.app.forge().resolve_version(loader).await?;
+            ServerType::Forge { .. } => {
+                let l = self.resolve_loader_version().await?.unwrap_or_default();
 
                 StartupMethod::Custom {
                     windows: vec![format!(
@@ -53,12 +53,28 @@ impl BuildContext<'_> {
     }
 
     pub async fn create_scripts(&self, startup: StartupMethod) -> Result<()> {
+        // the scripts still work without the java version check
+        let requirement = match self.app.java().requirement().await {
+            Ok(requirement) => requirement.filter(|_| !self.app.config.skip_java_check),
+            Err(e) => {
+                self.app.warn(format!(
+                    "Couldn't find the required java version, start scripts won't check it: {e}"
+                ));
+                None
+            }
+        };
+        let launcher = &self.app.server.launcher;
+        // detected installations only exist on this machine, keep `java` unless set explicitly
+        let java = launcher.get_java();
+
         fs::write(
             self.output_dir.join("start.bat"),
-            self.app
-                .server
-                .launcher
-                .generate_script_win(&self.app.server.name, &startup),
+            launcher.generate_script_win(
+                &java,
+                requirement.as_ref(),
+                &self.app.server.name,
+                &startup,
+            ),
         )
         .await?;
 
@@ -83,10 +99,8 @@ impl BuildContext<'_> {
         }
 
         file.write_all(
-            self.app
-                .server
-                .launcher
-                .generate_script_linux(&self.app.server.name, &startup)
+            launcher
+                .generate_script_linux(&java, requirement.as_ref(), &startup)
                 .as_bytes(),
         )?;
 
@@ -16,15 +16,16 @@ use tokio::{
 };
 
 use crate::{
-    model::{InstallMethod, ServerType},
-    sources::quilt,
+    app::ResolvedFile,
+    model::{InstallMethod, LockedServerJar, ServerType},
 };
 
 use super::BuildContext;
 
-impl<'a> BuildContext<'a> {
+impl BuildContext<'_> {
     pub async fn get_install_method(&self) -> Result<InstallMethod> {
         let mcver = self.app.mc_version();
+        let loader_version = self.resolve_loader_version().await?.unwrap_or_default();
         Ok(match self.app.server.jar.clone() {
             ServerType::Quilt { loader, .. } => {
                 let mut args = vec!["install", "server", mcver];
@@ -40,32 +41,25 @@ impl<'a> BuildContext<'a> {
                     label: "qsi",
                     args: args.into_iter().map(ToOwned::to_owned).collect(),
                     rename_from: Some("quilt-server-launch.jar".to_owned()),
-                    jar_name: format!(
-                        "quilt-server-launch-{mcver}-{}.jar",
-                        quilt::map_quilt_loader_version(&self.app.http_client, &loader)
-                            .await
-                            .context("resolving quilt loader version id (latest/latest-beta)")?
-                    ),
+                    jar_name: format!("quilt-server-launch-{mcver}-{loader_version}.jar"),
                 }
             }
-            ServerType::NeoForge { loader } => InstallMethod::Installer {
+            ServerType::NeoForge { .. } => InstallMethod::Installer {
                 name: "NeoForged Installer",
                 label: "nfi",
                 args: vec!["--installServer".to_owned(), ".".to_owned()],
                 rename_from: None,
                 jar_name: format!(
-                    "libraries/net/neoforged/forge/{mcver}-{0}/forge-{mcver}-{0}-server.jar",
-                    self.app.neoforge().resolve_version(&loader).await?
+                    "libraries/net/neoforged/forge/{mcver}-{loader_version}/forge-{mcver}-{loader_version}-server.jar"
                 ),
             },
-            ServerType::Forge { loader } => InstallMethod::Installer {
+            ServerType::Forge { .. } => InstallMethod::Installer {
                 name: "Forge Installer",
                 label: "fi",
                 args: vec!["--installServer".to_owned(), ".".to_owned()],
                 rename_from: None,
                 jar_name: format!(
-                    "libraries/net/minecraftforge/forge/{mcver}-{0}/forge-{mcver}-{0}-server.jar",
-                    self.app.forge().resolve_version(&loader).await?
+                    "libraries/net/minecraftforge/forge/{mcver}-{loader_version}/forge-{mcver}-{loader_version}-server.jar"
                 ),
             },
             ServerType::BuildTools { args, software } => {
@@ -98,7 +92,54 @@ impl<'a> BuildContext<'a> {
         })
     }
 
-    pub async fn download_server_jar(&'a self) -> Result<String> {
+    /// Name of the server jar in an already built output directory, using `self.lockfile`
+    pub async fn get_built_server_jar_name(&self) -> Result<String> {
+        Ok(match self.get_install_method().await? {
+            InstallMethod::Installer { jar_name, .. } => {
+                jar_name.replace("${mcver}", &self.app.server.mc_version)
+            }
+            InstallMethod::SingleJar => self
+                .lockfile
+                .server_jar
+                .as_ref()
+                .map(|s| s.resolved.filename.clone())
+                .context("The server jar isn't in .mcman.lock, build the server first")?,
+        })
+    }
+
+    /// Download the server jar (or its installer), reusing the committed lockfile if possible
+    async fn download_server_file(&self) -> Result<ResolvedFile> {
+        let (_, resolved) = if let Some(locked) = self.get_locked_server_jar() {
+            self.downloadable(&locked, "", None).await?
+        } else {
+            self.downloadable(&self.app.server.jar, "", None).await?
+        };
+
+        Ok(resolved)
+    }
+
+    /// Download the server jar (or its installer) and record it in `new_lockfile`
+    pub async fn fetch_server_jar(&mut self) -> Result<ResolvedFile> {
+        let resolved = self.download_server_file().await?;
+
+        self.new_lockfile.server_jar = Some(LockedServerJar {
+            mc_version: self.app.server.mc_version.clone(),
+            jar: self.app.server.jar.clone(),
+            resolved: resolved.clone(),
+            loader_version: None,
+        });
+
+        let loader_version = self.resolve_loader_version().await?;
+        if let Some(server_jar) = &mut self.new_lockfile.server_jar {
+            server_jar.loader_version = loader_version;
+        }
+
+        Ok(resolved)
+    }
+
+    pub async fn download_server_jar(&mut self) -> Result<String> {
+        let resolved = self.fetch_server_jar().await?;
+
         let serverjar_name = match self.get_install_method().await? {
             InstallMethod::Installer {
                 name,
@@ -107,8 +148,6 @@ impl<'a> BuildContext<'a> {
                 rename_from,
                 jar_name,
             } => {
-                let (_, resolved) = self.downloadable(&self.app.server.jar, "", None).await?;
-
                 let installer_jar = resolved.filename;
 
                 let jar_name = jar_name.replace("${mcver}", &self.app.server.mc_version);
@@ -123,6 +162,8 @@ impl<'a> BuildContext<'a> {
                         })
                         .dim()
                     ));
+                } else if self.app.config.offline {
+                    bail!("{name} needs internet access, build once without --offline first");
                 } else {
                     let pb = self.app.multi_progress.add(
                         ProgressBar::new_spinner()
@@ -188,12 +229,7 @@ impl<'a> BuildContext<'a> {
 
                 jar_name
             }
-            InstallMethod::SingleJar => {
-                self.downloadable(&self.app.server.jar, "", None)
-                    .await?
-                    .1
-                    .filename
-            }
+            InstallMethod::SingleJar => resolved.filename,
         };
 
         Ok(serverjar_name)
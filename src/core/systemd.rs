@@ -0,0 +1,238 @@
+use std::{fmt::Write, path::Path};
+
+use anyhow::{Context, Result};
+
+use crate::util::parse_size;
+
+use super::BuildContext;
+
+#[derive(Debug, Default, Clone)]
+pub struct SystemdOptions {
+    /// `User=` of the service
+    pub user: Option<String>,
+    /// Name of the network target the service belongs to
+    pub target: Option<String>,
+    /// Start the server with the java installation detected on this machine
+    /// instead of `launcher.get_java()`
+    pub detect_java: bool,
+}
+
+/// Name of the unit of a server or network, without the extension
+pub fn unit_name(name: &str) -> String {
+    let name = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect::<String>();
+
+    format!("mcman-{name}")
+}
+
+/// `%` and `$` are specifiers and variables in unit files
+fn escape(s: &str) -> String {
+    s.replace('%', "%%").replace('$', "$$")
+}
+
+/// Quote a single argument of `ExecStart=`/`ExecStop=`
+///
+/// Arguments with whitespace, quotes or backslashes are double quoted with C-style escapes
+fn quote(arg: &str) -> String {
+    let arg = escape(arg);
+
+    if arg == ";" {
+        return String::from("\\;");
+    }
+
+    if !arg.is_empty()
+        && !arg.contains(|c: char| c.is_whitespace() || matches!(c, '"' | '\'' | '\\'))
+    {
+        return arg;
+    }
+
+    let mut quoted = String::from('"');
+    for c in arg.chars() {
+        match c {
+            '"' | '\\' => {
+                quoted.push('\\');
+                quoted.push(c);
+            }
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+
+    quoted
+}
+
+/// Target that starts every server of a network together
+pub fn generate_target(network_name: &str, server_units: &[String]) -> String {
+    let wants = server_units
+        .iter()
+        .map(|unit| format!("{unit}.service"))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    format!(
+        "# generated by mcman\n\
+        [Unit]\n\
+        Description=Minecraft network {network_name}\n\
+        Wants={wants}\n\
+        \n\
+        [Install]\n\
+        WantedBy=multi-user.target\n"
+    )
+}
+
+impl BuildContext<'_> {
+    /// The `.service` and `.socket` units of the server as `(filename, content)`
+    ///
+    /// The socket is a FIFO connected to the console, so `ExecStop` can send the stop command
+    /// and other commands can be sent with `echo "say hi" > /run/mcman-<name>.stdin`
+    pub async fn generate_systemd_units(
+        &self,
+        serverjar_name: &str,
+        working_dir: &Path,
+        options: &SystemdOptions,
+    ) -> Result<Vec<(String, String)>> {
+        let server = &self.app.server;
+        let unit = unit_name(&server.name);
+        let fifo = format!("%t/{unit}.stdin");
+
+        let startup = self.get_startup_method(serverjar_name).await?;
+        // detected installations only exist on this machine, keep `java` unless asked to
+        let java = if options.detect_java {
+            let requirement = self.app.java().requirement().await.ok().flatten();
+            self.app.java().get_java_for(requirement.as_ref()).await
+        } else {
+            server.launcher.get_java()
+        };
+        let args = server.launcher.get_arguments(&startup, "linux");
+
+        let mut service = format!(
+            "# generated by mcman\n\
+            [Unit]\n\
+            Description=Minecraft server {}\n\
+            After=network-online.target\n\
+            Wants=network-online.target\n\
+            Requires={unit}.socket\n",
+            server.name
+        );
+
+        if let Some(target) = &options.target {
+            let _ = writeln!(service, "PartOf={target}.target");
+        }
+
+        let _ = write!(
+            service,
+            "\n\
+            [Service]\n\
+            Type=simple\n\
+            WorkingDirectory={}\n\
+            ExecStart={}\n\
+            ExecStop=/bin/sh -c 'printf \"%%s\\\\n\" \"$$1\" > {fifo}; while kill -0 $MAINPID 2>/dev/null; do sleep 1; done' sh {}\n\
+            Sockets={unit}.socket\n\
+            StandardInput=socket\n\
+            StandardOutput=journal\n\
+            StandardError=journal\n\
+            TimeoutStopSec=90\n\
+            SuccessExitStatus=0 143\n\
+            Restart=on-failure\n\
+            RestartSec=10\n",
+            working_dir.display().to_string().replace('%', "%%"),
+            std::iter::once(&java)
+                .chain(&args)
+                .map(|arg| quote(arg))
+                .collect::<Vec<_>>()
+                .join(" "),
+            quote(&server.options.stop_command),
+        );
+
+        if let Some(user) = &options.user {
+            let _ = writeln!(service, "User={user}");
+        }
+
+        // leave room for the memory the JVM uses outside of the heap
+        if !server.launcher.memory.is_empty() {
+            let heap = parse_size(&server.launcher.memory)
+                .context(format!("Parsing launcher.memory '{}'", server.launcher.memory))?
+                >> 20;
+            let _ = writeln!(service, "MemoryHigh={}M", heap * 5 / 4);
+            let _ = writeln!(service, "MemoryMax={}M", heap * 3 / 2);
+        }
+
+        let _ = write!(
+            service,
+            "\n\
+            [Install]\n\
+            WantedBy={}\n",
+            options
+                .target
+                .as_ref()
+                .map_or("multi-user.target".to_owned(), |t| format!("{t}.target"))
+        );
+
+        let mut socket = format!(
+            "# generated by mcman\n\
+            [Unit]\n\
+            Description=Console of Minecraft server {}\n\
+            PartOf={unit}.service\n\
+            \n\
+            [Socket]\n\
+            ListenFIFO={fifo}\n\
+            SocketMode=0660\n\
+            RemoveOnStop=true\n",
+            server.name
+        );
+
+        if let Some(user) = &options.user {
+            let _ = writeln!(socket, "SocketUser={user}");
+        }
+
+        Ok(vec![
+            (format!("{unit}.service"), service),
+            (format!("{unit}.socket"), socket),
+        ])
+    }
+
+    /// Write the units next to the start scripts, for `launcher.systemd`
+    pub async fn create_systemd_units(&self, serverjar_name: &str) -> Result<()> {
+        let working_dir = self
+            .output_dir
+            .canonicalize()
+            .context("Resolving the output directory")?;
+
+        for (filename, content) in self
+            .generate_systemd_units(serverjar_name, &working_dir, &SystemdOptions::default())
+            .await?
+        {
+            tokio::fs::write(self.output_dir.join(&filename), content)
+                .await
+                .context(format!("Writing {filename}"))?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quoting() {
+        assert_eq!(quote("-Xmx2G"), "-Xmx2G");
+        assert_eq!(quote("/opt/my java/bin/java"), "\"/opt/my java/bin/java\"");
+        assert_eq!(quote("say \"hi\" 100%"), "\"say \\\"hi\\\" 100%%\"");
+        assert_eq!(quote("C:\\java"), "\"C:\\\\java\"");
+        assert_eq!(quote("$HOME"), "$$HOME");
+        assert_eq!(quote(""), "\"\"");
+        assert_eq!(quote(";"), "\\;");
+    }
+}
@@ -8,12 +8,15 @@ use std::{
 use anyhow::{Context, Result};
 use indicatif::{ProgressBar, ProgressIterator, ProgressStyle};
 
-use crate::model::World;
+use crate::{
+    app::ResolvedFile,
+    model::{Downloadable, World},
+};
 
 use super::BuildContext;
 
 impl BuildContext<'_> {
-    pub async fn process_worlds(&self) -> Result<()> {
+    pub async fn process_worlds(&mut self) -> Result<()> {
         let progress_bar = self.app.multi_progress.add(
             ProgressBar::new(self.app.server.worlds.len() as u64)
                 .with_style(ProgressStyle::with_template(
@@ -24,6 +27,8 @@ impl BuildContext<'_> {
 
         self.app.ci("::group::Worlds");
 
+        let mut world_files = vec![];
+
         for (name, world) in self
             .app
             .server
@@ -33,11 +38,15 @@ impl BuildContext<'_> {
         {
             progress_bar.set_message(name.clone());
 
-            self.process_world(&progress_bar, name, world)
-                .await
-                .context(format!("Processing world: {name}"))?;
+            world_files.extend(
+                self.process_world(&progress_bar, name, world)
+                    .await
+                    .context(format!("Processing world: {name}"))?,
+            );
         }
 
+        self.new_lockfile.world_files.extend(world_files);
+
         self.app.ci("::endgroup::");
 
         Ok(())
@@ -48,14 +57,20 @@ impl BuildContext<'_> {
         progress_bar: &ProgressBar,
         name: &str,
         world: &World,
-    ) -> Result<()> {
+    ) -> Result<Vec<(Downloadable, ResolvedFile)>> {
+        let mut world_files = vec![];
+
         if !self.world_exists_in_output(name)? {
             if self.world_source_exists(name) {
                 self.app.worlds().unpack(name)?;
             } else if let Some(dl) = &world.download {
-                let (path, _resolved) = self
-                    .downloadable(dl, ".mcman-cache", Some(progress_bar))
-                    .await?;
+                let (path, resolved) = if let Some(locked) = self.get_locked_world_file(dl) {
+                    self.downloadable(&locked, ".mcman-cache", Some(progress_bar)).await?
+                } else {
+                    self.downloadable(dl, ".mcman-cache", Some(progress_bar)).await?
+                };
+
+                world_files.push((dl.clone(), resolved));
 
                 let spinner = self.app.multi_progress.insert_after(
                     progress_bar,
@@ -68,18 +83,29 @@ impl BuildContext<'_> {
 
                 spinner.finish_with_message("Unzipped world successfully".to_string());
             }
+        } else if let Some(dl) = &world.download {
+            // keep the previous resolution so the lockfiles don't forget about it
+            let previous = self
+                .get_locked_world_file(dl)
+                .or_else(|| self.lockfile.find_world_file(dl).cloned());
+
+            if let Some(resolved) = previous {
+                world_files.push((dl.clone(), resolved));
+            }
         }
 
         if !world.datapacks.is_empty() {
             fs::create_dir_all(self.output_dir.join(name).join("datapacks"))
                 .context(format!("Failed to create {name}/datapacks directory"))?;
 
-            self.process_datapacks(progress_bar, name, world)
-                .await
-                .context("Processing datapacks")?;
+            world_files.extend(
+                self.process_datapacks(progress_bar, name, world)
+                    .await
+                    .context("Processing datapacks")?,
+            );
         }
 
-        Ok(())
+        Ok(world_files)
     }
 
     pub fn world_source_exists(&self, name: &str) -> bool {
@@ -104,7 +130,7 @@ impl BuildContext<'_> {
         progress_bar: &ProgressBar,
         name: &str,
         world: &World,
-    ) -> Result<()> {
+    ) -> Result<Vec<(Downloadable, ResolvedFile)>> {
         let pb = self.app.multi_progress.insert_after(
             progress_bar,
             ProgressBar::new(world.datapacks.len() as u64)
@@ -114,12 +140,20 @@ impl BuildContext<'_> {
                 .with_message("Processing datapacks..."),
         );
 
+        let mut resolved_files = vec![];
+
         for dp in world.datapacks.iter().progress_with(pb.clone()) {
             let path = format!("{name}/datapacks");
-            self.downloadable(dp, &path, Some(&pb)).await?;
+            let (_, resolved) = if let Some(locked) = self.get_locked_world_file(dp) {
+                self.downloadable(&locked, &path, Some(&pb)).await?
+            } else {
+                self.downloadable(dp, &path, Some(&pb)).await?
+            };
+
+            resolved_files.push((dp.clone(), resolved));
         }
 
-        Ok(())
+        Ok(resolved_files)
     }
 }
 
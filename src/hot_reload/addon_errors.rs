@@ -0,0 +1,227 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    path::Path,
+};
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use crate::{
+    app::{AddonType, ResolvedFile},
+    interop::jar::read_jar_metadata,
+    model::{Downloadable, Lockfile},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AddonErrorKind {
+    /// The jar couldn't be loaded at all
+    LoadFailed,
+    /// Loaded but threw while enabling
+    EnableFailed,
+    /// A required dependency is missing or incompatible
+    MissingDependency,
+    /// Exception from the mod loader
+    ModLoading,
+}
+
+impl Display for AddonErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::LoadFailed => "failed to load",
+            Self::EnableFailed => "failed to enable",
+            Self::MissingDependency => "has missing dependencies",
+            Self::ModLoading => "errored while loading",
+        })
+    }
+}
+
+/// An addon error found in the server log
+#[derive(Debug, Clone)]
+pub struct AddonError {
+    pub kind: AddonErrorKind,
+    /// Plugin name, mod id or jar filename, as printed in the log
+    pub name: String,
+    pub line: String,
+}
+
+lazy_static! {
+    static ref PATTERNS: Vec<(AddonErrorKind, Regex)> = vec![
+        // bukkit/spigot/paper
+        (
+            AddonErrorKind::LoadFailed,
+            Regex::new(r"Could not load (?:plugin )?'(?:plugins[/\\])?([^']+\.jar)'").unwrap(),
+        ),
+        (
+            AddonErrorKind::EnableFailed,
+            Regex::new(r"Error occurred while enabling (\S+)").unwrap(),
+        ),
+        (
+            AddonErrorKind::MissingDependency,
+            Regex::new(r"Unknown/missing dependency plugins: \[([^\],]+)").unwrap(),
+        ),
+        // velocity/bungeecord
+        (
+            AddonErrorKind::LoadFailed,
+            Regex::new(r"Unable to load plugin (?:plugins[/\\])?(\S+\.jar)").unwrap(),
+        ),
+        (
+            AddonErrorKind::EnableFailed,
+            Regex::new(r"Can't create plugin (\S+)").unwrap(),
+        ),
+        (
+            AddonErrorKind::EnableFailed,
+            Regex::new(r"Exception encountered when loading plugin: (\S+)").unwrap(),
+        ),
+        // fabric/quilt
+        (
+            AddonErrorKind::MissingDependency,
+            Regex::new(r"Mod '[^']+' \(([\w-]+)\) [^ ]+ requires").unwrap(),
+        ),
+        (
+            AddonErrorKind::ModLoading,
+            Regex::new(r"Could not execute entrypoint stage '\w+' due to errors, provided by '([\w-]+)'").unwrap(),
+        ),
+        // forge/neoforge
+        (
+            AddonErrorKind::MissingDependency,
+            Regex::new(r"Mod ID: '([\w-]+)', Requested by: '([\w-]+)'").unwrap(),
+        ),
+        (
+            AddonErrorKind::ModLoading,
+            Regex::new(r"(?:Caught exception during event \S+ dispatch for modid|Failed to create mod instance\. ModID:) ([\w-]+)").unwrap(),
+        ),
+    ];
+}
+
+impl AddonError {
+    /// Check a single log line against the known patterns
+    pub fn detect(line: &str) -> Option<Self> {
+        PATTERNS.iter().find_map(|(kind, regex)| {
+            let caps = regex.captures(line)?;
+            // for forge dependencies, the mod that requested the dependency is the broken one
+            let name = caps.get(2).or(caps.get(1))?.as_str();
+
+            Some(Self {
+                kind: *kind,
+                name: name.to_owned(),
+                line: line.to_owned(),
+            })
+        })
+    }
+
+    /// Find the addon this error belongs to
+    ///
+    /// Uses the filename, the plugin name or mod id declared by the jars in `output_dir`,
+    /// then the start of the filename
+    pub fn find_addon<'a>(
+        &self,
+        lockfile: &'a Lockfile,
+        output_dir: &Path,
+    ) -> Option<(AddonType, &'a Downloadable, &'a ResolvedFile)> {
+        if normalize(&self.name).is_empty() {
+            return None;
+        }
+
+        let addons = [AddonType::Plugin, AddonType::Mod]
+            .into_iter()
+            .flat_map(|addon_type| {
+                lockfile
+                    .addons(addon_type)
+                    .iter()
+                    .map(move |(dl, resolved)| (addon_type, dl, resolved))
+            })
+            .collect::<Vec<_>>();
+
+        let declares_name = |addon_type: AddonType, resolved: &ResolvedFile| {
+            let path = output_dir
+                .join(addon_type.folder())
+                .join(&resolved.filename);
+            read_jar_metadata(&path).is_ok_and(|metadata| {
+                metadata.iter().any(|m| {
+                    std::iter::once(&m.name)
+                        .chain(&m.provides)
+                        .any(|name| name.eq_ignore_ascii_case(&self.name))
+                })
+            })
+        };
+
+        addons
+            .iter()
+            .find(|(_, _, resolved)| resolved.filename == self.name)
+            .or_else(|| {
+                addons
+                    .iter()
+                    .find(|(addon_type, _, resolved)| declares_name(*addon_type, resolved))
+            })
+            .or_else(|| {
+                addons
+                    .iter()
+                    .find(|(_, _, resolved)| filename_starts_with(&resolved.filename, &self.name))
+            })
+            .copied()
+    }
+}
+
+/// Lowercase alphanumerics only, `Via-Version-4.9.2.jar` => `viaversion492jar`
+fn normalize(s: &str) -> String {
+    s.chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+/// Whether the filename starts with `name`, ending on a word boundary
+///
+/// `Via-Version-4.9.2.jar` matches `ViaVersion`, but `createaddition-1.2.jar` doesn't match `create`
+fn filename_starts_with(filename: &str, name: &str) -> bool {
+    let name = normalize(name);
+    let mut prefix = String::new();
+
+    for word in filename.split(|c: char| !c.is_ascii_alphanumeric()) {
+        prefix.push_str(&normalize(word));
+
+        if prefix == name {
+            return true;
+        }
+
+        if !name.starts_with(&prefix) {
+            return false;
+        }
+    }
+
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filename_boundaries() {
+        assert!(filename_starts_with("Via-Version-4.9.2.jar", "ViaVersion"));
+        assert!(filename_starts_with("create-1.20.1-0.5.1.jar", "create"));
+        assert!(filename_starts_with("fabric-api-0.92.0.jar", "fabric-api"));
+        assert!(filename_starts_with(
+            "sodium-fabric-mc1.20.1-0.5.3.jar",
+            "sodium"
+        ));
+
+        assert!(!filename_starts_with(
+            "createaddition-1.20.1-1.2.jar",
+            "create"
+        ));
+        assert!(!filename_starts_with("Essentials-2.20.jar", "EssentialsX"));
+        assert!(!filename_starts_with("create-1.20.1.jar", ""));
+    }
+
+    #[test]
+    fn detect_forge_requester() {
+        let err = AddonError::detect(
+            "Mod ID: 'flywheel', Requested by: 'create', Expected range: '[0.6.10,0.6.11)'",
+        )
+        .unwrap();
+
+        assert_eq!(err.kind, AddonErrorKind::MissingDependency);
+        assert_eq!(err.name, "create");
+    }
+}
@@ -0,0 +1,70 @@
+use std::{env, time::Duration};
+
+use anyhow::{Context, Result};
+use tokio::net::TcpStream;
+
+use crate::{
+    core::BuildContext,
+    protocol::{parse_address, parse_properties, rcon::RconClient, rcon_settings},
+};
+
+/// A server that `mcman dev --attach` sends commands to over RCON, instead of a child process
+pub struct AttachedServer {
+    pub host: String,
+    pub port: u16,
+    password: String,
+    client: Option<RconClient<TcpStream>>,
+}
+
+impl AttachedServer {
+    /// The RCON settings come from `config/server.properties` with the variables replaced,
+    /// so `rcon.password=${RCON_PASSWORD}` works like it does while building
+    pub fn new(builder: &BuildContext<'_>, address: &str) -> Result<Self> {
+        let path = builder.app.server.path.join("config").join("server.properties");
+        let content = std::fs::read_to_string(&path)
+            .or_else(|_| std::fs::read_to_string(builder.output_dir.join("server.properties")))
+            .context(format!("Reading {}", path.display()))?;
+        let props = parse_properties(&builder.bootstrap_content(&content));
+
+        let (rcon_port, password) = rcon_settings(&props)?;
+        let (host, port) = parse_address(address, rcon_port)?;
+        let password = env::var("MCMAN_RCON_PASSWORD")
+            .ok()
+            .or(password)
+            .context("No RCON password, set rcon.password or $MCMAN_RCON_PASSWORD")?;
+
+        Ok(Self {
+            host,
+            port,
+            password,
+            client: None,
+        })
+    }
+
+    /// Run a command, reconnecting if the server restarted since the last one
+    pub async fn command(&mut self, command: &str) -> Result<String> {
+        if let Some(client) = &mut self.client {
+            if let Ok(output) = client.command(command).await {
+                return Ok(output);
+            }
+        }
+
+        self.client = None;
+
+        let mut client = self.connect().await?;
+        let output = client.command(command).await?;
+        self.client = Some(client);
+
+        Ok(output)
+    }
+
+    pub async fn connect(&self) -> Result<RconClient<TcpStream>> {
+        RconClient::connect(
+            &self.host,
+            self.port,
+            &self.password,
+            Duration::from_secs(5),
+        )
+        .await
+    }
+}
@@ -0,0 +1,296 @@
+use lazy_static::lazy_static;
+use regex::{Captures, Regex};
+
+use crate::sources::mclogs::{
+    AnalysisEntry, AnalysisLine, LogAnalysis, LogInsights, Problem, Solution,
+};
+
+/// A known problem, recognized by a single log line
+struct Rule {
+    regex: Regex,
+    message: fn(&Captures) -> String,
+    solutions: fn(&Captures) -> Vec<String>,
+}
+
+impl Rule {
+    fn new(
+        regex: &str,
+        message: fn(&Captures) -> String,
+        solutions: fn(&Captures) -> Vec<String>,
+    ) -> Self {
+        Self {
+            regex: Regex::new(regex).unwrap(),
+            message,
+            solutions,
+        }
+    }
+}
+
+/// Java 8 is class file version 52
+fn java_version(class_file_version: &str) -> String {
+    class_file_version
+        .parse::<usize>()
+        .map_or(class_file_version.to_owned(), |v| v.saturating_sub(44).to_string())
+}
+
+lazy_static! {
+    static ref RULES: Vec<Rule> = vec![
+        Rule::new(
+            r"class file version (\d+)\.\d+\), this version of the Java Runtime only recognizes class file versions up to (\d+)",
+            |c| format!(
+                "Java {} is required, but the server was started with Java {}",
+                java_version(&c[1]),
+                java_version(&c[2])
+            ),
+            |c| vec![
+                format!("Install Java {} or newer", java_version(&c[1])),
+                format!(
+                    "Set `launcher.java_version = \"{}\"` in server.toml and point `JAVA_{}_BIN` to it",
+                    java_version(&c[1]),
+                    java_version(&c[1])
+                ),
+            ],
+        ),
+        Rule::new(
+            r"requires (?:version \S+ or later|any version) of (?:java|Java)\b",
+            |_| "A mod requires a newer Java version".to_owned(),
+            |_| vec!["Install a newer Java version and set `launcher.java_version` in server.toml".to_owned()],
+        ),
+        Rule::new(
+            r"Mod '([^']+)' \(([\w-]+)\) \S+ requires (?:any version|version \S+(?: or later)?) of (?:mod '[^']+' \()?'?([\w-]+)'?\)?, which is missing",
+            |c| format!("The mod '{}' requires '{}', which is missing", &c[1], &c[3]),
+            |c| vec![format!("Add '{}' to the mods of server.toml", &c[3])],
+        ),
+        Rule::new(
+            r"Mod ID: '([\w-]+)', Requested by: '([\w-]+)', Expected range: '([^']*)'",
+            |c| format!("The mod '{}' requires '{}' ({}), which is missing", &c[2], &c[1], &c[3]),
+            |c| vec![format!("Add a version of '{}' matching {} to the mods of server.toml", &c[1], &c[3])],
+        ),
+        Rule::new(
+            r"Unknown/missing dependency plugins: \[([^\]]+)\]",
+            |c| format!("Plugin dependencies are missing: {}", &c[1]),
+            |c| vec![format!("Add {} to the plugins of server.toml", &c[1])],
+        ),
+        Rule::new(
+            r"FAILED TO BIND TO PORT|BindException: Address already in use",
+            |_| "The server port is already in use".to_owned(),
+            |_| vec![
+                "Stop the other process that is using the port".to_owned(),
+                "Use another port by changing `SERVER_PORT` in the variables of server.toml".to_owned(),
+            ],
+        ),
+        Rule::new(
+            r"You need to agree to the EULA in order to run the server",
+            |_| "The Minecraft EULA hasn't been accepted".to_owned(),
+            |_| vec![
+                "Set `launcher.eula_args = true` in server.toml".to_owned(),
+                "Write `eula=true` to eula.txt in the config folder".to_owned(),
+            ],
+        ),
+        Rule::new(
+            r"java\.lang\.OutOfMemoryError: (.+)",
+            |c| format!("The server ran out of memory ({})", c[1].trim()),
+            |_| vec![
+                "Give the server more memory with `launcher.memory` in server.toml".to_owned(),
+                "Remove addons or lower view-distance to use less memory".to_owned(),
+            ],
+        ),
+    ];
+
+    static ref LINE_PREFIX: Regex =
+        Regex::new(r"^\[(\d{2}:\d{2}:\d{2})\] \[[^\]]*?/(\w+)\]: ").unwrap();
+}
+
+/// Monolog levels, like mclo.gs uses
+fn level(name: &str) -> usize {
+    match name {
+        "FATAL" => 2,
+        "ERROR" => 3,
+        "WARN" => 4,
+        "DEBUG" => 7,
+        _ => 6,
+    }
+}
+
+fn entry(number: usize, line: &str) -> AnalysisEntry {
+    let (time, level_name, prefix) = match LINE_PREFIX.captures(line) {
+        Some(c) => (
+            Some(c[1].to_owned()),
+            c.get(2).map_or("", |m| m.as_str()),
+            c[0].to_owned(),
+        ),
+        None => (None, "", String::new()),
+    };
+
+    AnalysisEntry {
+        level: level(level_name),
+        time,
+        prefix,
+        lines: vec![AnalysisLine {
+            number,
+            content: line.to_owned(),
+        }],
+    }
+}
+
+/// Analyze a log locally, without uploading it anywhere
+///
+/// Problems are counted once per distinct message, the entry is the first line that matched
+pub fn analyze_log(name: &str, lines: &[String]) -> LogInsights {
+    let mut problems: Vec<Problem> = vec![];
+
+    for (idx, line) in lines.iter().enumerate() {
+        let Some((rule, caps)) = RULES
+            .iter()
+            .find_map(|rule| Some((rule, rule.regex.captures(line)?)))
+        else {
+            continue;
+        };
+
+        let message = (rule.message)(&caps);
+
+        if let Some(problem) = problems.iter_mut().find(|p| p.message == message) {
+            problem.counter += 1;
+            continue;
+        }
+
+        problems.push(Problem {
+            message,
+            counter: 1,
+            entry: entry(idx + 1, line),
+            solutions: (rule.solutions)(&caps)
+                .into_iter()
+                .map(|message| Solution { message })
+                .collect(),
+        });
+    }
+
+    LogInsights {
+        id: String::new(),
+        name: name.to_owned(),
+        log_type: "mcman".to_owned(),
+        version: env!("CARGO_PKG_VERSION").to_owned(),
+        title: format!("{name} ({} problems)", problems.len()),
+        analysis: LogAnalysis {
+            problems,
+            information: vec![],
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn problem(line: &str) -> Option<Problem> {
+        analyze_log("test", &[line.to_owned()])
+            .analysis
+            .problems
+            .into_iter()
+            .next()
+    }
+
+    fn message(line: &str) -> Option<String> {
+        problem(line).map(|p| p.message)
+    }
+
+    #[test]
+    fn java_versions() {
+        assert_eq!(
+            message(
+                "java.lang.UnsupportedClassVersionError: net/minecraft/bundler/Main has been compiled \
+                by a more recent version of the Java Runtime (class file version 65.0), this version \
+                of the Java Runtime only recognizes class file versions up to 61.0"
+            )
+            .as_deref(),
+            Some("Java 21 is required, but the server was started with Java 17")
+        );
+        assert_eq!(
+            message(" - Mod 'Sodium' (sodium) 0.5.3 requires version 17 or later of java, which is missing!")
+                .as_deref(),
+            Some("A mod requires a newer Java version")
+        );
+    }
+
+    #[test]
+    fn missing_dependencies() {
+        assert_eq!(
+            message(
+                " - Mod 'Create Fabric' (create) 0.5.1-f requires any version of mod \
+                'Fabric API' (fabric-api), which is missing!"
+            )
+            .as_deref(),
+            Some("The mod 'Create Fabric' requires 'fabric-api', which is missing")
+        );
+        assert_eq!(
+            message(" - Mod 'Create' (create) 0.5.1 requires version 0.6.10 of 'flywheel', which is missing!")
+                .as_deref(),
+            Some("The mod 'Create' requires 'flywheel', which is missing")
+        );
+        assert_eq!(
+            message(
+                "Mod ID: 'flywheel', Requested by: 'create', Expected range: '[0.6.10,0.6.11)'"
+            )
+            .as_deref(),
+            Some("The mod 'create' requires 'flywheel' ([0.6.10,0.6.11)), which is missing")
+        );
+        assert_eq!(
+            message(
+                "org.bukkit.plugin.UnknownDependencyException: Unknown/missing dependency plugins: \
+                [Vault, LuckPerms]. Please download and install these plugins to run 'Essentials'."
+            )
+            .as_deref(),
+            Some("Plugin dependencies are missing: Vault, LuckPerms")
+        );
+    }
+
+    #[test]
+    fn server_problems() {
+        assert_eq!(
+            message("[12:00:00] [Server thread/WARN]: **** FAILED TO BIND TO PORT!").as_deref(),
+            Some("The server port is already in use")
+        );
+        assert_eq!(
+            message("java.net.BindException: Address already in use").as_deref(),
+            Some("The server port is already in use")
+        );
+        assert_eq!(
+            message(
+                "[12:00:00] [ServerMain/INFO]: You need to agree to the EULA in order to run the \
+                server. Go to eula.txt for more info."
+            )
+            .as_deref(),
+            Some("The Minecraft EULA hasn't been accepted")
+        );
+        assert_eq!(
+            message("java.lang.OutOfMemoryError: Java heap space").as_deref(),
+            Some("The server ran out of memory (Java heap space)")
+        );
+        assert_eq!(
+            message("[12:00:00] [Server thread/INFO]: Done (3.2s)!"),
+            None
+        );
+    }
+
+    #[test]
+    fn entries_and_counters() {
+        let lines = [
+            "[12:00:00] [Server thread/INFO]: Starting minecraft server",
+            "[12:00:01] [Server thread/WARN]: **** FAILED TO BIND TO PORT!",
+            "[12:00:02] [Server thread/WARN]: **** FAILED TO BIND TO PORT!",
+        ]
+        .map(ToOwned::to_owned);
+
+        let problems = analyze_log("test", &lines).analysis.problems;
+
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].counter, 2);
+        assert_eq!(problems[0].entry.level, 4);
+        assert_eq!(problems[0].entry.time.as_deref(), Some("12:00:01"));
+        assert_eq!(
+            problems[0].entry.prefix,
+            "[12:00:01] [Server thread/WARN]: "
+        );
+        assert_eq!(problems[0].entry.lines[0].number, 2);
+    }
+}
@@ -3,16 +3,15 @@ use std::{
     env,
     ffi::OsStr,
     path::{Component, PathBuf},
-    process,
     process::{ExitStatus, Stdio},
     sync::{Arc, Mutex},
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use anyhow::{anyhow, bail, Context, Result};
 use console::style;
 use dialoguer::theme::ColorfulTheme;
-use indicatif::ProgressBar;
+use indicatif::{MultiProgress, ProgressBar};
 use notify_debouncer_full::{
     new_debouncer,
     notify::{RecommendedWatcher, RecursiveMode, Watcher},
@@ -20,17 +19,32 @@ use notify_debouncer_full::{
 };
 use pathdiff::diff_paths;
 use tokio::{
-    io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines},
-    process::{Child, ChildStdin, ChildStdout},
+    io::{AsyncBufRead, AsyncBufReadExt, AsyncWriteExt, BufReader, Lines},
+    process::{Child, ChildStderr, ChildStdin, ChildStdout},
     sync::mpsc,
+    task::JoinHandle,
 };
 
-use crate::core::BuildContext;
+use crate::{
+    core::BuildContext,
+    model::{is_default_stop_command, SoftwareType},
+    protocol::{server_address, slp},
+};
 
-use self::config::{HotReloadAction, HotReloadConfig};
+use self::{
+    addon_errors::AddonError,
+    attach::AttachedServer,
+    config::{HotReloadAction, HotReloadConfig},
+    log_analysis::analyze_log,
+    smoke_test::SmokeTest,
+};
 
+pub mod addon_errors;
+pub mod attach;
 pub mod config;
+pub mod log_analysis;
 pub mod pattern_serde;
+pub mod smoke_test;
 
 #[derive(Debug)]
 pub struct DevSession<'a> {
@@ -40,6 +54,14 @@ pub struct DevSession<'a> {
     pub hot_reload: Option<Arc<Mutex<HotReloadConfig>>>,
     // true if in test mode (exit server after server fully starts, report/upload logs on fail)
     pub test_mode: bool,
+    // printed before every console line, used when running multiple servers at once
+    pub console_prefix: Option<String>,
+    // false if commands are only sent through the channel (see `start_with`)
+    pub read_stdin: bool,
+    // where to write the JUnit report of test mode
+    pub junit: Option<PathBuf>,
+    // address of an already running server to send commands to over RCON (`--attach`)
+    pub attach: Option<String>,
 }
 
 #[derive(Debug)]
@@ -59,12 +81,19 @@ pub enum Command {
     SendCommand(String),
     Log(String),
     WaitUntilExit,
+    NextCheck,
+    /// The server finished starting, from the success line or a ping
+    Ready,
+    /// Restart the server for a hot reload action
+    Restart,
+    /// Connect to the server of `--attach` instead of building and starting one
+    Attach,
     Bootstrap(PathBuf, PathBuf),
     BootstrapGroup(String, PathBuf, PathBuf),
 }
 
-async fn try_read_line(
-    opt: &mut Option<tokio::io::Lines<BufReader<tokio::process::ChildStdout>>>,
+async fn try_read_line<R: AsyncBufRead + Unpin>(
+    opt: &mut Option<Lines<R>>,
 ) -> Result<Option<String>> {
     Ok(match opt {
         Some(lines) => lines.next_line().await?,
@@ -72,6 +101,55 @@ async fn try_read_line(
     })
 }
 
+/// Forward the stderr of the server to the console, keeping the lines for the log analysis
+async fn collect_stderr(
+    stderr: ChildStderr,
+    console_log: Arc<Mutex<Vec<String>>>,
+    addon_errors: Option<(Arc<Mutex<Vec<AddonError>>>, Arc<Mutex<State>>)>,
+    mp: MultiProgress,
+    line_prefix: String,
+) {
+    let mut lines = BufReader::new(stderr).lines();
+
+    while let Ok(Some(line)) = lines.next_line().await {
+        let s = line.trim_end();
+        console_log.lock().unwrap().push(s.to_owned());
+        if let Some((addon_errors, state)) = &addon_errors {
+            if !matches!(*state.lock().unwrap(), State::Stopping) {
+                addon_errors
+                    .lock()
+                    .unwrap()
+                    .extend(AddonError::detect(s.trim()));
+            }
+        }
+        mp.suspend(|| {
+            eprintln!("{line_prefix}{s}");
+        });
+    }
+}
+
+/// Ping the server until it answers, for `tests.ping`
+async fn wait_for_ping(host: String, port: u16, tx: mpsc::Sender<Command>) {
+    loop {
+        tokio::time::sleep(Duration::from_secs(2)).await;
+
+        if slp::ping(&host, port, Duration::from_secs(5)).await.is_ok() {
+            let _ = tx
+                .send(Command::Log(format!("Server answered a ping on {host}:{port}")))
+                .await;
+            let _ = tx.send(Command::Ready).await;
+            break;
+        }
+    }
+}
+
+async fn sleep_until(deadline: Option<Instant>) {
+    match deadline {
+        Some(deadline) => tokio::time::sleep_until(deadline.into()).await,
+        None => std::future::pending().await,
+    }
+}
+
 async fn try_wait_child(opt: &mut Option<Child>) -> Result<Option<ExitStatus>> {
     Ok(match opt {
         Some(c) => Some(c.wait().await?),
@@ -96,6 +174,19 @@ pub enum TestResult {
 pub const LINE_CRASHED: &str = "]: Crashed! The full crash report has been saved to";
 
 impl DevSession<'_> {
+    /// `options.stop_command`, proxies don't understand the default `stop`
+    pub fn stop_command(&self) -> &str {
+        let options = &self.builder.app.server.options;
+
+        if is_default_stop_command(&options.stop_command)
+            && self.builder.app.server.jar.get_software_type() == SoftwareType::Proxy
+        {
+            "end"
+        } else {
+            &options.stop_command
+        }
+    }
+
     pub async fn spawn_child(&mut self) -> Result<Child> {
         let platform = if env::consts::FAMILY == "windows" {
             "windows"
@@ -106,7 +197,24 @@ impl DevSession<'_> {
         let server_jar = self.jar_name.as_ref().unwrap().clone();
         let startup = self.builder.get_startup_method(&server_jar).await?;
         let launcher = &self.builder.app.server.launcher;
-        let java = launcher.get_java();
+        // like the start scripts, the server can still be started without the check
+        let requirement = match self.builder.app.java().requirement().await {
+            Ok(requirement) => requirement,
+            Err(e) => {
+                self.builder.app.warn(format!(
+                    "Couldn't find the required java version, not checking it: {e}"
+                ));
+                None
+            }
+        };
+        let java = self.builder.app.java().get_java_for(requirement.as_ref()).await;
+
+        if let Some(requirement) = &requirement {
+            if !self.builder.app.config.skip_java_check {
+                self.builder.app.java().check(&java, requirement).await?;
+            }
+        }
+
         let args = launcher.get_arguments(&startup, platform);
 
         self.builder
@@ -126,7 +234,12 @@ impl DevSession<'_> {
             .current_dir(&dir)
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
-            .stderr(Stdio::inherit())
+            // collected for the log analysis in test mode
+            .stderr(if self.test_mode {
+                Stdio::piped()
+            } else {
+                Stdio::inherit()
+            })
             .spawn()?)
     }
 
@@ -137,7 +250,7 @@ impl DevSession<'_> {
         mut self,
         mut rx: mpsc::Receiver<Command>,
         tx: mpsc::Sender<Command>,
-    ) -> Result<()> {
+    ) -> Result<TestResult> {
         let mp = self.builder.app.multi_progress.clone();
 
         let mut child: Option<Child> = None;
@@ -148,10 +261,29 @@ impl DevSession<'_> {
         let mut is_session_ending = false;
         let mut test_result = TestResult::Failed;
         let mut exit_status = None;
+        let mut smoke_test = SmokeTest::new(&self.builder.app.server.tests)?;
+        let mut started_at = Instant::now();
+        // plugins/mods that failed to load, only collected in test mode, from stdout and stderr
+        let addon_errors: Arc<Mutex<Vec<AddonError>>> = Arc::default();
+        // console output of the last run, only collected in test mode
+        let console_log: Arc<Mutex<Vec<String>>> = Arc::default();
+        // set once the test is decided and the server is being stopped
+        let mut test_stopping = false;
+        // pings the server until it answers, for `tests.ping`
+        let mut ping_task: Option<JoinHandle<()>> = None;
+        // the server of `--attach`, commands are sent to it instead of a child process
+        let mut attached: Option<AttachedServer> = None;
 
         let state = Arc::new(Mutex::new(State::Stopped));
 
-        let mut stdin_lines = BufReader::new(tokio::io::stdin()).lines();
+        let mut stdin_lines = self
+            .read_stdin
+            .then(|| BufReader::new(tokio::io::stdin()).lines());
+
+        let line_prefix = match &self.console_prefix {
+            Some(prefix) => format!("{prefix} {}", style("| ").bold()),
+            None => style("| ").bold().to_string(),
+        };
 
         'l: loop {
             tokio::select! {
@@ -161,20 +293,79 @@ impl DevSession<'_> {
                             self.builder.app.ci("::group::Starting server process");
                             self.builder.app.log_dev("Starting server process...");
                             if child.is_none() {
+                                started_at = Instant::now();
                                 let mut spawned_child = self.spawn_child().await?;
                                 stdout_lines = Some(tokio::io::BufReader::new(spawned_child.stdout.take().expect("child stdout None")).lines());
+                                console_log.lock().unwrap().clear();
+                                if let Some(stderr) = spawned_child.stderr.take() {
+                                    tokio::spawn(collect_stderr(
+                                        stderr,
+                                        console_log.clone(),
+                                        self.test_mode.then(|| (addon_errors.clone(), state.clone())),
+                                        mp.clone(),
+                                        line_prefix.clone(),
+                                    ));
+                                }
                                 child_stdin = Some(spawned_child.stdin.take().expect("child stdin None"));
                                 child = Some(spawned_child);
+                                if self.test_mode && self.builder.app.server.tests.ping {
+                                    let (host, port) = server_address(self.builder.app, &self.builder.output_dir);
+                                    if let Some(task) = ping_task.replace(tokio::spawn(wait_for_ping(host, port, tx.clone()))) {
+                                        task.abort();
+                                    }
+                                }
                                 let mut lock = state.lock().unwrap();
                                 *lock = State::Starting;
                             }
                         }
                         Command::SendCommand(command) => {
-                            self.builder.app.log_dev(format!("$ {}", command.trim()));
-                            if let Some(ref mut stdin) = &mut child_stdin {
-                                stdin.write_all(command.as_bytes()).await?;
+                            if let Some(server) = &mut attached {
+                                for cmd in command.lines().map(str::trim).filter(|c| !c.is_empty()) {
+                                    self.builder.app.log_dev(format!("$ {cmd}"));
+                                    match server.command(cmd).await {
+                                        Ok(output) => mp.suspend(|| {
+                                            for line in output.lines() {
+                                                println!("{line_prefix}{line}");
+                                            }
+                                        }),
+                                        Err(e) => self.builder.app.warn(format!("RCON: {e:#}")),
+                                    }
+                                }
+                            } else {
+                                self.builder.app.log_dev(format!("$ {}", command.trim()));
+                                if let Some(ref mut stdin) = &mut child_stdin {
+                                    stdin.write_all(command.as_bytes()).await?;
+                                }
                             }
                         }
+                        Command::Restart => {
+                            if attached.is_some() {
+                                self.builder.app.warn("mcman can't restart an attached server, restart it to apply the changes");
+                            } else {
+                                tx.send(Command::SendCommand(format!("{}\n", self.stop_command()))).await?;
+                                tx.send(Command::WaitUntilExit).await?;
+                                tx.send(Command::Start).await?;
+                            }
+                        }
+                        Command::Attach => {
+                            let address = self.attach.clone().unwrap_or_default();
+                            let server = AttachedServer::new(&self.builder, &address)?;
+                            server.connect().await.context(format!(
+                                "Attaching to {}:{} over RCON",
+                                server.host, server.port
+                            ))?;
+                            self.builder.app.log_dev(format!(
+                                "Attached to {}:{} over RCON",
+                                server.host, server.port
+                            ));
+                            attached = Some(server);
+
+                            let mut lock = state.lock().unwrap();
+                            *lock = State::Online;
+                            drop(lock);
+
+                            self.builder.bootstrap_files().await?;
+                        }
                         Command::Log(message) => {
                             self.builder.app.log_dev(message);
                         }
@@ -190,12 +381,11 @@ impl DevSession<'_> {
                                         // future to keep printing logs
                                         loop {
                                             if let Ok(Some(line)) = try_read_line(&mut stdout_lines).await {
+                                                if self.test_mode {
+                                                    console_log.lock().unwrap().push(line.clone());
+                                                }
                                                 mp.suspend(|| {
-                                                    println!(
-                                                        "{}{}",
-                                                        style("| ").bold(),
-                                                        line.trim()
-                                                    );
+                                                    println!("{line_prefix}{}", line.trim());
                                                 });
                                             }
                                         }
@@ -225,6 +415,9 @@ impl DevSession<'_> {
                             child = None;
                             stdout_lines = None;
                             child_stdin = None;
+                            if let Some(task) = ping_task.take() {
+                                task.abort();
+                            }
                             self.builder.app.log_dev("Server process ended");
                         }
                         Command::Rebuild => {
@@ -238,7 +431,7 @@ impl DevSession<'_> {
                                 Err(e) => {
                                     self.builder.app.error("Error while reloading server.toml");
                                     self.builder.app.error(e);
-                                    return Ok(());
+                                    return Ok(TestResult::Failed);
                                 }
                             }
 
@@ -247,7 +440,18 @@ impl DevSession<'_> {
                                 Err(e) => {
                                     self.builder.app.error("Error while reloading network.toml");
                                     self.builder.app.error(e);
-                                    return Ok(());
+                                    return Ok(TestResult::Failed);
+                                }
+                            }
+
+                            if self.test_mode {
+                                addon_errors.lock().unwrap().clear();
+                                match SmokeTest::new(&self.builder.app.server.tests) {
+                                    Ok(t) => smoke_test = t,
+                                    Err(e) => {
+                                        self.builder.app.error(format!("{e:?}"));
+                                        return Ok(TestResult::Failed);
+                                    }
                                 }
                             }
 
@@ -259,7 +463,7 @@ impl DevSession<'_> {
                                 Err(e) => {
                                     self.builder.app.error("Error while building");
                                     self.builder.app.error(e);
-                                    return Ok(());
+                                    return Ok(TestResult::Failed);
                                 }
                             };
                         }
@@ -282,6 +486,60 @@ impl DevSession<'_> {
                                 - Err: {e}", full_path.display(), rel_path.display()));
                             }
                         }
+                        Command::NextCheck => {
+                            if let Some(case) = smoke_test.cases.last() {
+                                match &case.failure {
+                                    None => self.builder.app.success(format!("Check passed: {}", case.name)),
+                                    Some(failure) => self.builder.app.warn(format!("Check failed: {} ({failure})", case.name)),
+                                }
+                            }
+
+                            if let Some(check) = smoke_test.start_next() {
+                                self.builder.app.log_dev(format!("Checking: {}", check.get_name()));
+
+                                if let Some(command) = check.command.clone() {
+                                    tx.send(Command::SendCommand(format!("{command}\n"))).await?;
+                                }
+                            } else {
+                                test_stopping = true;
+
+                                if smoke_test.passed() {
+                                    self.builder.app.success("Test passed!");
+                                } else {
+                                    test_result = TestResult::Failed;
+                                }
+
+                                let mut lock = state.lock().unwrap();
+                                *lock = State::Stopping;
+                                drop(lock);
+
+                                tx.send(Command::SendCommand(format!(
+                                    "{}\n",
+                                    self.stop_command()
+                                ))).await?;
+                                tx.send(Command::WaitUntilExit).await?;
+                                tx.send(Command::EndSession).await?;
+                            }
+                        }
+                        Command::Ready => {
+                            if self.test_mode
+                                && test_result == TestResult::Failed
+                                && !is_stopping
+                                && !test_stopping {
+                                test_result = TestResult::Success;
+
+                                if let Some(task) = ping_task.take() {
+                                    task.abort();
+                                }
+
+                                let mut lock = state.lock().unwrap();
+                                *lock = State::Online;
+                                drop(lock);
+
+                                smoke_test.record_startup(started_at.elapsed());
+                                tx.send(Command::NextCheck).await?;
+                            }
+                        }
                         Command::EndSession => {
                             self.builder.app.log_dev("Ending session...");
                             self.builder.app.ci("::endgroup::");
@@ -294,25 +552,18 @@ impl DevSession<'_> {
 
                     if self.test_mode
                         && !is_stopping
-                        && test_result == TestResult::Failed {
-                        if s.contains(&self.builder.app.server.options.success_line) /* && s.ends_with("For help, type \"help\"") */ {
-                            test_result = TestResult::Success;
-
-                            let mut lock = state.lock().unwrap();
-                            *lock = State::Online;
-                            drop(lock);
-
-                            self.builder.app.success("Test passed!");
+                        && !test_stopping {
+                        if let Some(err) = AddonError::detect(s) {
+                            addon_errors.lock().unwrap().push(err);
+                        }
 
-                            tx.send(Command::SendCommand(format!(
-                                "{}\n",
-                                &self.builder.app.server.options.stop_command
-                            ))).await?;
-                            tx.send(Command::WaitUntilExit).await?;
-                            tx.send(Command::EndSession).await?;
+                        if test_result == TestResult::Failed
+                            && s.contains(&self.builder.app.server.options.success_line) /* && s.ends_with("For help, type \"help\"") */ {
+                            tx.send(Command::Ready).await?;
                         } else if s.contains(LINE_CRASHED) || s == "---- end of report ----" {
                             self.builder.app.warn("Server crashed!");
                             test_result = TestResult::Crashed;
+                            test_stopping = true;
 
                             let mut lock = state.lock().unwrap();
                             *lock = State::Stopping;
@@ -320,19 +571,27 @@ impl DevSession<'_> {
 
                             tx.send(Command::WaitUntilExit).await?;
                             tx.send(Command::EndSession).await?;
+                        } else if smoke_test.on_line(s) {
+                            tx.send(Command::NextCheck).await?;
                         }
                     }
 
+                    if self.test_mode {
+                        console_log.lock().unwrap().push(s.to_owned());
+                    }
+
                     mp.suspend(|| {
-                        println!(
-                            "{}{s}",
-                            style("| ").bold()
-                        );
+                        println!("{line_prefix}{s}");
                     });
                 },
-                Ok(Some(line)) = stdin_lines.next_line() => {
+                Ok(Some(line)) = try_read_line(&mut stdin_lines) => {
                     let cmd = line.trim();
 
+                    if attached.is_some() {
+                        tx.send(Command::SendCommand(format!("{cmd}\n"))).await?;
+                        continue 'l;
+                    }
+
                     self.builder.app.log_dev(format!("$ {cmd}"));
 
                     if let Some(ref mut stdin) = &mut child_stdin {
@@ -341,6 +600,10 @@ impl DevSession<'_> {
                         self.builder.app.log_dev("Server offline");
                     }
                 },
+                () = sleep_until(smoke_test.deadline()) => {
+                    smoke_test.on_timeout();
+                    tx.send(Command::NextCheck).await?;
+                },
                 Ok(Some(status)) = try_wait_child(&mut child) => {
                     exit_status = Some(status);
                     self.builder.app.ci("::endgroup::");
@@ -350,20 +613,26 @@ impl DevSession<'_> {
                     child = None;
                     stdout_lines = None;
                     child_stdin = None;
+                    if let Some(task) = ping_task.take() {
+                        task.abort();
+                    }
 
                     if self.test_mode {
                         tx.send(Command::EndSession).await?;
                     }
                 },
                 _ = tokio::signal::ctrl_c() => {
-                    if is_session_ending {
+                    if attached.is_some() {
+                        self.builder.app.log_dev("Detaching from the server...");
+                        break 'l;
+                    } else if is_session_ending {
                         self.builder.app.log_dev("Force-stopping development session...");
                         break 'l;
                     } else if !is_stopping {
                         is_session_ending = true;
                         self.builder.app.log_dev("Stopping development session...");
 
-                        tx.send(Command::SendCommand("stop\nend\n".to_owned())).await?;
+                        tx.send(Command::SendCommand(format!("{}\n", self.stop_command()))).await?;
                         tx.send(Command::WaitUntilExit).await?;
                         tx.send(Command::EndSession).await?;
                     }
@@ -373,6 +642,10 @@ impl DevSession<'_> {
 
         // end of loop > tokio::select!
 
+        if let Some(task) = ping_task.take() {
+            task.abort();
+        }
+
         if let Some(ref mut child) = &mut child {
             self.builder.app.info("Killing undead child process...");
             child.kill().await?;
@@ -381,10 +654,33 @@ impl DevSession<'_> {
         self.builder.app.ci("::endgroup::");
 
         if self.test_mode {
+            let addon_errors = std::mem::take(&mut *addon_errors.lock().unwrap());
+
+            smoke_test.fail_remaining(if test_result == TestResult::Crashed {
+                "Server crashed"
+            } else {
+                "Server stopped"
+            });
+
+            for err in &addon_errors {
+                smoke_test.record_failure(format!("addon: {}", err.name), err.line.clone());
+            }
+
+            if test_result == TestResult::Success && !smoke_test.passed() {
+                test_result = TestResult::Failed;
+            }
+
+            if let Some(path) = &self.junit {
+                std::fs::write(path, smoke_test.to_junit(&self.builder.app.server.name))
+                    .context(format!("Writing JUnit report to {}", path.display()))?;
+                self.builder
+                    .app
+                    .log(format!("  - JUnit report written to {}", path.display()));
+            }
+
             match test_result {
                 TestResult::Success => {
                     self.builder.app.success("Test passed");
-                    process::exit(0);
                 }
                 TestResult::Crashed | TestResult::Failed => {
                     mp.suspend(|| {
@@ -408,6 +704,62 @@ impl DevSession<'_> {
                         if let TestResult::Crashed = test_result {
                             println!("  - Server crashed");
                         }
+
+                        for case in &smoke_test.cases {
+                            if let Some(failure) = &case.failure {
+                                if !case.name.starts_with("addon: ") {
+                                    println!("  - Check '{}' failed: {failure}", case.name);
+                                }
+                            }
+                        }
+
+                        if !addon_errors.is_empty() {
+                            println!("  - {} addon(s) failed:", addon_errors.len());
+                        }
+
+                        for err in &addon_errors {
+                            match err.find_addon(&self.builder.new_lockfile, &self.builder.output_dir) {
+                                Some((addon_type, dl, resolved)) => println!(
+                                    "    - {} {} ({}/{}, {})",
+                                    style(&err.name).bold(),
+                                    err.kind,
+                                    addon_type.folder(),
+                                    resolved.filename,
+                                    dl.to_short_string(),
+                                ),
+                                None => println!(
+                                    "    - {} {} (not managed by mcman)",
+                                    style(&err.name).bold(),
+                                    err.kind,
+                                ),
+                            }
+                        }
+
+                        let insights = analyze_log("console", &console_log.lock().unwrap());
+
+                        for problem in &insights.analysis.problems {
+                            println!(
+                                "  - {}{}",
+                                style(&problem.message).bold(),
+                                if problem.counter > 1 {
+                                    format!(" (x{})", problem.counter)
+                                } else {
+                                    String::new()
+                                }
+                            );
+
+                            for line in &problem.entry.lines {
+                                println!(
+                                    "    {} {}",
+                                    style(format!("{}:", line.number)).dim(),
+                                    style(line.content.trim()).dim()
+                                );
+                            }
+
+                            for solution in &problem.solutions {
+                                println!("    => {}", solution.message);
+                            }
+                        }
                     });
 
                     if self.builder.app.config.services.mclogs.enabled {
@@ -483,13 +835,13 @@ impl DevSession<'_> {
                             });
                         }
                     }
-
-                    process::exit(1);
                 }
             }
+
+            return Ok(test_result);
         }
 
-        Ok(())
+        Ok(TestResult::Success)
     }
 
     pub fn create_hotreload_watcher(
@@ -564,11 +916,7 @@ impl DevSession<'_> {
                                 .expect("tx send err");
                             }
                             HotReloadAction::Restart => {
-                                tx.blocking_send(Command::SendCommand("stop\nend\n".to_owned()))
-                                    .expect("tx send err");
-                                tx.blocking_send(Command::WaitUntilExit)
-                                    .expect("tx send err");
-                                tx.blocking_send(Command::Start).expect("tx send err");
+                                tx.blocking_send(Command::Restart).expect("tx send err");
                             }
                             HotReloadAction::RunCommand(cmd) => {
                                 tx.blocking_send(Command::SendCommand(format!("{cmd}\n")))
@@ -641,11 +989,7 @@ impl DevSession<'_> {
                                 .expect("tx send err");
                             }
                             HotReloadAction::Restart => {
-                                tx.blocking_send(Command::SendCommand("stop\nend\n".to_owned()))
-                                    .expect("tx send err");
-                                tx.blocking_send(Command::WaitUntilExit)
-                                    .expect("tx send err");
-                                tx.blocking_send(Command::Start).expect("tx send err");
+                                tx.blocking_send(Command::Restart).expect("tx send err");
                             }
                             HotReloadAction::RunCommand(cmd) => {
                                 tx.blocking_send(Command::SendCommand(format!("{cmd}\n")))
@@ -660,6 +1004,7 @@ impl DevSession<'_> {
 
     pub fn create_restarter_watcher(
         tx: mpsc::Sender<Command>,
+        stop_command: String,
     ) -> Result<Debouncer<RecommendedWatcher, FileIdMap>> {
         Ok(new_debouncer(
             Duration::from_secs(1),
@@ -669,7 +1014,7 @@ impl DevSession<'_> {
                     if !e.iter().any(|e| e.kind.is_modify()) {
                         return;
                     }
-                    tx.blocking_send(Command::SendCommand("stop\nend".to_owned()))
+                    tx.blocking_send(Command::SendCommand(format!("{stop_command}\n")))
                         .expect("tx send err");
                     tx.blocking_send(Command::WaitUntilExit)
                         .expect("tx send err");
@@ -679,9 +1024,21 @@ impl DevSession<'_> {
         )?)
     }
 
-    pub async fn start(self) -> Result<()> {
+    /// Build and run the server until the session ends
+    ///
+    /// Outside of test mode, the result is always `TestResult::Success`
+    pub async fn start(self) -> Result<TestResult> {
         let (tx, rx) = mpsc::channel(32);
 
+        self.start_with(tx, rx).await
+    }
+
+    /// Like `start`, but other tasks can send commands to the session through `tx`
+    pub async fn start_with(
+        self,
+        tx: mpsc::Sender<Command>,
+        rx: mpsc::Receiver<Command>,
+    ) -> Result<TestResult> {
         let cfg_mutex_w = self.hot_reload.clone().unwrap_or_default();
 
         let mut config_watcher = Self::create_config_watcher(
@@ -691,8 +1048,10 @@ impl DevSession<'_> {
         )?;
         let mut hotreload_watcher =
             Self::create_hotreload_watcher(cfg_mutex_w.clone(), tx.clone())?;
-        let mut servertoml_watcher = Self::create_restarter_watcher(tx.clone())?;
-        let mut networktoml_watcher = Self::create_restarter_watcher(tx.clone())?;
+        let stop_command = self.stop_command().to_owned();
+        let mut servertoml_watcher =
+            Self::create_restarter_watcher(tx.clone(), stop_command.clone())?;
+        let mut networktoml_watcher = Self::create_restarter_watcher(tx.clone(), stop_command)?;
         let mut network_groups_watcher = Self::create_network_groups_watcher(
             cfg_mutex_w.clone(),
             tx.clone(),
@@ -710,11 +1069,14 @@ impl DevSession<'_> {
                 self.builder.app.server.path.join("config").as_path(),
                 RecursiveMode::Recursive,
             )?;
-            self.builder.app.log_dev("Watching server.toml");
-            servertoml_watcher.watcher().watch(
-                self.builder.app.server.path.join("server.toml").as_path(),
-                RecursiveMode::NonRecursive,
-            )?;
+            // rebuilding would start a server next to the attached one
+            if self.attach.is_none() {
+                self.builder.app.log_dev("Watching server.toml");
+                servertoml_watcher.watcher().watch(
+                    self.builder.app.server.path.join("server.toml").as_path(),
+                    RecursiveMode::NonRecursive,
+                )?;
+            }
             self.builder.app.log_dev("Watching hotrload.toml");
             hotreload_watcher.watcher().watch(
                 self.builder
@@ -733,17 +1095,21 @@ impl DevSession<'_> {
                 network_groups_watcher
                     .watcher()
                     .watch(&nw.path.join("groups"), RecursiveMode::Recursive)?;
-                networktoml_watcher.watcher().watch(
-                    nw.path.join("network.toml").as_path(),
-                    RecursiveMode::NonRecursive,
-                )?;
+                if self.attach.is_none() {
+                    networktoml_watcher.watcher().watch(
+                        nw.path.join("network.toml").as_path(),
+                        RecursiveMode::NonRecursive,
+                    )?;
+                }
             }
         }
 
-        tx.send(Command::Rebuild).await?;
-
-        self.handle_commands(rx, tx.clone()).await?;
+        if self.attach.is_some() {
+            tx.send(Command::Attach).await?;
+        } else {
+            tx.send(Command::Rebuild).await?;
+        }
 
-        Ok(())
+        self.handle_commands(rx, tx.clone()).await
     }
 }
@@ -0,0 +1,265 @@
+use std::{
+    collections::VecDeque,
+    fmt::Write,
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result};
+use regex::Regex;
+
+use crate::model::{SmokeTestCheck, SmokeTestOptions};
+
+pub const STARTUP_CHECK: &str = "startup";
+
+#[derive(Debug, Clone)]
+pub struct TestCase {
+    pub name: String,
+    pub elapsed: Duration,
+    pub failure: Option<String>,
+}
+
+#[derive(Debug)]
+struct RunningCheck {
+    check: SmokeTestCheck,
+    regex: Regex,
+    started: Instant,
+    deadline: Instant,
+}
+
+/// Runs the `[tests]` checks of server.toml one by one against the console output
+#[derive(Debug)]
+pub struct SmokeTest {
+    default_timeout: Duration,
+    pending: VecDeque<(SmokeTestCheck, Regex)>,
+    current: Option<RunningCheck>,
+    pub cases: Vec<TestCase>,
+}
+
+impl SmokeTest {
+    /// Compiles every regex beforehand so typos are reported before the server is built
+    pub fn new(options: &SmokeTestOptions) -> Result<Self> {
+        let pending = options
+            .checks
+            .iter()
+            .map(|check| {
+                let regex = Regex::new(&check.expect)
+                    .context(format!("Invalid regex in test check '{}'", check.get_name()))?;
+                Ok((check.clone(), regex))
+            })
+            .collect::<Result<VecDeque<_>>>()?;
+
+        Ok(Self {
+            default_timeout: Duration::from_secs(options.timeout),
+            pending,
+            current: None,
+            cases: vec![],
+        })
+    }
+
+    pub fn record_startup(&mut self, elapsed: Duration) {
+        self.cases.push(TestCase {
+            name: STARTUP_CHECK.to_owned(),
+            elapsed,
+            failure: None,
+        });
+    }
+
+    /// Record a failure that isn't tied to a check, like an addon failing to load
+    pub fn record_failure(&mut self, name: String, failure: String) {
+        self.cases.push(TestCase {
+            name,
+            elapsed: Duration::ZERO,
+            failure: Some(failure),
+        });
+    }
+
+    /// Start the next check, `None` if every check has been run
+    pub fn start_next(&mut self) -> Option<&SmokeTestCheck> {
+        let (check, regex) = self.pending.pop_front()?;
+        let started = Instant::now();
+        let timeout = check.timeout.map_or(self.default_timeout, Duration::from_secs);
+
+        self.current = Some(RunningCheck {
+            check,
+            regex,
+            started,
+            deadline: started + timeout,
+        });
+
+        self.current.as_ref().map(|c| &c.check)
+    }
+
+    /// When the running check times out
+    pub fn deadline(&self) -> Option<Instant> {
+        self.current.as_ref().map(|c| c.deadline)
+    }
+
+    /// Returns true if the line finished the running check
+    pub fn on_line(&mut self, line: &str) -> bool {
+        if !self.current.as_ref().is_some_and(|c| c.regex.is_match(line)) {
+            return false;
+        }
+
+        self.finish_current(None);
+        true
+    }
+
+    pub fn on_timeout(&mut self) {
+        if let Some(current) = &self.current {
+            let failure = format!(
+                "Timed out after {}s waiting for /{}/",
+                (current.deadline - current.started).as_secs(),
+                current.regex.as_str()
+            );
+            self.finish_current(Some(failure));
+        }
+    }
+
+    fn finish_current(&mut self, failure: Option<String>) {
+        if let Some(current) = self.current.take() {
+            self.cases.push(TestCase {
+                name: current.check.get_name().to_owned(),
+                elapsed: current.started.elapsed(),
+                failure,
+            });
+        }
+    }
+
+    /// Fail the startup check (if it didn't pass), the running check and every check that didn't run
+    pub fn fail_remaining(&mut self, reason: &str) {
+        if !self.cases.iter().any(|c| c.name == STARTUP_CHECK) {
+            self.cases.push(TestCase {
+                name: STARTUP_CHECK.to_owned(),
+                elapsed: Duration::ZERO,
+                failure: Some(reason.to_owned()),
+            });
+        }
+
+        self.finish_current(Some(reason.to_owned()));
+
+        for (check, _) in self.pending.drain(..) {
+            self.cases.push(TestCase {
+                name: check.get_name().to_owned(),
+                elapsed: Duration::ZERO,
+                failure: Some(format!("Not run: {reason}")),
+            });
+        }
+    }
+
+    pub fn passed(&self) -> bool {
+        self.cases.iter().all(|c| c.failure.is_none())
+    }
+
+    pub fn to_junit(&self, suite: &str) -> String {
+        let failures = self.cases.iter().filter(|c| c.failure.is_some()).count();
+        let time = self.cases.iter().map(|c| c.elapsed).sum::<Duration>();
+
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<testsuites>\n");
+
+        let _ = writeln!(
+            xml,
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{failures}\" time=\"{:.3}\">",
+            escape_xml(suite),
+            self.cases.len(),
+            time.as_secs_f64()
+        );
+
+        for case in &self.cases {
+            let _ = write!(
+                xml,
+                "    <testcase name=\"{}\" classname=\"{}\" time=\"{:.3}\"",
+                escape_xml(&case.name),
+                escape_xml(suite),
+                case.elapsed.as_secs_f64()
+            );
+
+            if let Some(failure) = &case.failure {
+                let _ = writeln!(
+                    xml,
+                    ">\n      <failure message=\"{}\"/>\n    </testcase>",
+                    escape_xml(failure)
+                );
+            } else {
+                xml.push_str("/>\n");
+            }
+        }
+
+        xml.push_str("  </testsuite>\n</testsuites>\n");
+        xml
+    }
+}
+
+fn escape_xml(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // parsers turn raw newlines in attributes into spaces
+            '\n' => escaped.push_str("&#10;"),
+            '\r' => escaped.push_str("&#13;"),
+            '\t' => escaped.push_str("&#9;"),
+            // other control characters, like color codes of the console, aren't allowed in XML
+            c if c.is_control() => {}
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn junit_report() {
+        let options = SmokeTestOptions {
+            checks: vec![SmokeTestCheck {
+                name: Some("list <players>".to_owned()),
+                command: Some("list".to_owned()),
+                expect: "players online".to_owned(),
+                timeout: None,
+            }],
+            ..Default::default()
+        };
+
+        let mut test = SmokeTest::new(&options).unwrap();
+        test.record_startup(Duration::from_millis(1500));
+        test.record_failure(
+            "addon: Foo&Bar".to_owned(),
+            "Could not load 'plugins/a\"b.jar' <x>".to_owned(),
+        );
+        test.fail_remaining("Server stopped");
+
+        assert_eq!(
+            test.to_junit("lobby"),
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+            <testsuites>\n  \
+              <testsuite name=\"lobby\" tests=\"3\" failures=\"2\" time=\"1.500\">\n    \
+                <testcase name=\"startup\" classname=\"lobby\" time=\"1.500\"/>\n    \
+                <testcase name=\"addon: Foo&amp;Bar\" classname=\"lobby\" time=\"0.000\">\n      \
+                  <failure message=\"Could not load &apos;plugins/a&quot;b.jar&apos; &lt;x&gt;\"/>\n    \
+                </testcase>\n    \
+                <testcase name=\"list &lt;players&gt;\" classname=\"lobby\" time=\"0.000\">\n      \
+                  <failure message=\"Not run: Server stopped\"/>\n    \
+                </testcase>\n  \
+              </testsuite>\n\
+            </testsuites>\n"
+        );
+    }
+
+    #[test]
+    fn xml_escaping() {
+        assert_eq!(
+            escape_xml("a < b && c > \"d\""),
+            "a &lt; b &amp;&amp; c &gt; &quot;d&quot;"
+        );
+        assert_eq!(escape_xml("first\nsecond\ttab"), "first&#10;second&#9;tab");
+        assert_eq!(escape_xml("\u{1b}[31mError\u{1b}[0m"), "[31mError[0m");
+    }
+}
@@ -0,0 +1,343 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    fs::File,
+    io::Read,
+    path::Path,
+};
+
+use anyhow::{Context, Result};
+use serde_json::Value;
+
+use crate::model::ServerType;
+
+mod range;
+mod yaml;
+
+pub use range::VersionRange;
+use yaml::Yaml;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JarPlatform {
+    Bukkit,
+    Paper,
+    BungeeCord,
+    Velocity,
+    Fabric,
+    Quilt,
+    Forge,
+    NeoForge,
+}
+
+impl JarPlatform {
+    /// Check if the server can load jars of this platform, `true` for unknown software
+    pub fn runs_on(self, server: &ServerType) -> bool {
+        match server {
+            ServerType::Downloadable { .. } => true,
+            ServerType::Vanilla {} => false,
+            ServerType::Fabric { .. } => self == Self::Fabric,
+            ServerType::Quilt { .. } => matches!(self, Self::Fabric | Self::Quilt),
+            ServerType::Forge { .. } => self == Self::Forge,
+            // NeoForge for 1.20.1 still loads Forge mods
+            ServerType::NeoForge { .. } => matches!(self, Self::Forge | Self::NeoForge),
+            ServerType::Velocity {} => self == Self::Velocity,
+            // BungeeCord falls back to plugin.yml when there's no bungee.yml
+            ServerType::BungeeCord {} | ServerType::Waterfall {} => {
+                matches!(self, Self::BungeeCord | Self::Bukkit)
+            }
+            ServerType::PaperMC { project, .. } => match project.as_str() {
+                "velocity" => self == Self::Velocity,
+                "waterfall" => matches!(self, Self::BungeeCord | Self::Bukkit),
+                _ => matches!(self, Self::Bukkit | Self::Paper),
+            },
+            ServerType::Paper {} | ServerType::Purpur { .. } => {
+                matches!(self, Self::Bukkit | Self::Paper)
+            }
+            ServerType::BuildTools { software, .. } => {
+                self == Self::Bukkit || (self == Self::Paper && software == "paper")
+            }
+        }
+    }
+
+    /// Loader version of the server if it's the loader of this platform
+    pub fn loader_version(self, server: &ServerType) -> Option<&str> {
+        match (self, server) {
+            (Self::Fabric, ServerType::Fabric { loader, .. })
+            | (Self::Quilt, ServerType::Quilt { loader, .. })
+            | (Self::Forge, ServerType::Forge { loader })
+            | (Self::NeoForge, ServerType::NeoForge { loader }) => {
+                Some(loader.as_str()).filter(|l| *l != "latest")
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Display for JarPlatform {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Bukkit => "Bukkit plugin",
+            Self::Paper => "Paper plugin",
+            Self::BungeeCord => "BungeeCord plugin",
+            Self::Velocity => "Velocity plugin",
+            Self::Fabric => "Fabric mod",
+            Self::Quilt => "Quilt mod",
+            Self::Forge => "Forge mod",
+            Self::NeoForge => "NeoForge mod",
+        })
+    }
+}
+
+/// What a plugin or mod declares about itself in its descriptor file
+#[derive(Debug, Clone)]
+pub struct JarMetadata {
+    pub platform: JarPlatform,
+    /// Plugin name or mod id
+    pub name: String,
+    /// Other names plugins can depend on this one with
+    pub provides: Vec<String>,
+    /// Names of required plugins, not collected for mods
+    pub depends: Vec<String>,
+    pub minecraft: Option<VersionRange>,
+    pub loader: Option<VersionRange>,
+}
+
+type DescriptorParser = fn(&str) -> Vec<JarMetadata>;
+
+/// Descriptor files, in the order loaders prefer them
+const DESCRIPTORS: &[(&str, DescriptorParser)] = &[
+    ("paper-plugin.yml", parse_paper_plugin_yml),
+    ("bungee.yml", parse_bungee_yml),
+    ("plugin.yml", parse_plugin_yml),
+    ("velocity-plugin.json", parse_velocity_plugin_json),
+    ("quilt.mod.json", parse_quilt_mod_json),
+    ("fabric.mod.json", parse_fabric_mod_json),
+    ("META-INF/neoforge.mods.toml", parse_mods_toml),
+    ("META-INF/mods.toml", parse_mods_toml),
+];
+
+/// Read every descriptor in a jar, empty if it has none (like libraries).
+/// Descriptors that fail to parse are skipped
+pub fn read_jar_metadata(path: &Path) -> Result<Vec<JarMetadata>> {
+    let file = File::open(path).context(format!("Opening {}", path.display()))?;
+    let mut archive = zip::ZipArchive::new(file).context("Opening jar")?;
+
+    let mut found = vec![];
+    for (descriptor, parse) in DESCRIPTORS {
+        let Ok(mut entry) = archive.by_name(descriptor) else {
+            continue;
+        };
+
+        let mut content = String::new();
+        entry
+            .read_to_string(&mut content)
+            .context(format!("Reading {descriptor}"))?;
+
+        let mut metadata = parse(content.trim_start_matches('\u{feff}'));
+        if *descriptor == "META-INF/neoforge.mods.toml" {
+            for meta in &mut metadata {
+                meta.platform = JarPlatform::NeoForge;
+            }
+        }
+
+        found.extend(metadata);
+    }
+
+    Ok(found)
+}
+
+fn yaml_plugin(platform: JarPlatform, yaml: &Yaml, depends_key: &str) -> Vec<JarMetadata> {
+    let Some(name) = yaml.get("name").and_then(Yaml::as_str) else {
+        return vec![];
+    };
+
+    vec![JarMetadata {
+        platform,
+        name: name.to_owned(),
+        provides: yaml
+            .get("provides")
+            .map(Yaml::as_str_list)
+            .unwrap_or_default(),
+        depends: yaml
+            .get(depends_key)
+            .map(Yaml::as_str_list)
+            .unwrap_or_default(),
+        minecraft: yaml
+            .get("api-version")
+            .and_then(Yaml::as_str)
+            .filter(|v| !v.is_empty())
+            .map(|v| VersionRange::AtLeast(v.to_owned())),
+        loader: None,
+    }]
+}
+
+fn parse_plugin_yml(content: &str) -> Vec<JarMetadata> {
+    yaml_plugin(JarPlatform::Bukkit, &Yaml::parse(content), "depend")
+}
+
+fn parse_bungee_yml(content: &str) -> Vec<JarMetadata> {
+    yaml_plugin(JarPlatform::BungeeCord, &Yaml::parse(content), "depends")
+}
+
+fn parse_paper_plugin_yml(content: &str) -> Vec<JarMetadata> {
+    let yaml = Yaml::parse(content);
+    let mut metadata = yaml_plugin(JarPlatform::Paper, &yaml, "depend");
+
+    let is_required = |dep: &Yaml| dep.get("required").and_then(Yaml::as_str) != Some("false");
+
+    for meta in &mut metadata {
+        meta.depends = match yaml.get("dependencies") {
+            // dependencies: { server: { Name: { required: true } } }
+            Some(deps @ Yaml::Map(_)) => deps
+                .get("server")
+                .map(Yaml::entries)
+                .unwrap_or_default()
+                .iter()
+                .filter(|(_, dep)| is_required(dep))
+                .map(|(name, _)| name.clone())
+                .collect(),
+            // older format: dependencies: [ { name: Name, required: true } ]
+            Some(Yaml::List(deps)) => deps
+                .iter()
+                .filter(|dep| is_required(dep))
+                .filter_map(|dep| dep.get("name").and_then(Yaml::as_str))
+                .map(ToOwned::to_owned)
+                .collect(),
+            _ => vec![],
+        };
+    }
+
+    metadata
+}
+
+fn parse_velocity_plugin_json(content: &str) -> Vec<JarMetadata> {
+    let Ok(json) = serde_json::from_str::<Value>(content) else {
+        return vec![];
+    };
+    let Some(id) = json["id"].as_str() else {
+        return vec![];
+    };
+
+    vec![JarMetadata {
+        platform: JarPlatform::Velocity,
+        name: id.to_owned(),
+        provides: vec![],
+        depends: json["dependencies"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter(|dep| !dep["optional"].as_bool().unwrap_or(false))
+            .filter_map(|dep| dep["id"].as_str())
+            .map(ToOwned::to_owned)
+            .collect(),
+        minecraft: None,
+        loader: None,
+    }]
+}
+
+/// A version predicate string or an array of them
+fn json_predicates(value: &Value) -> Option<VersionRange> {
+    let predicates = match value {
+        Value::String(s) => vec![s.clone()],
+        Value::Array(items) => items
+            .iter()
+            .filter_map(Value::as_str)
+            .map(ToOwned::to_owned)
+            .collect(),
+        _ => return None,
+    };
+
+    (!predicates.is_empty()).then_some(VersionRange::Predicates(predicates))
+}
+
+fn parse_fabric_mod_json(content: &str) -> Vec<JarMetadata> {
+    let Ok(json) = serde_json::from_str::<Value>(content) else {
+        return vec![];
+    };
+    let Some(id) = json["id"].as_str() else {
+        return vec![];
+    };
+
+    vec![JarMetadata {
+        platform: JarPlatform::Fabric,
+        name: id.to_owned(),
+        provides: vec![],
+        depends: vec![],
+        minecraft: json_predicates(&json["depends"]["minecraft"]),
+        loader: json_predicates(&json["depends"]["fabricloader"]),
+    }]
+}
+
+fn parse_quilt_mod_json(content: &str) -> Vec<JarMetadata> {
+    let Ok(json) = serde_json::from_str::<Value>(content) else {
+        return vec![];
+    };
+    let loader = &json["quilt_loader"];
+    let Some(id) = loader["id"].as_str() else {
+        return vec![];
+    };
+
+    let depends = loader["depends"].as_array().cloned().unwrap_or_default();
+    let versions = |dep_id: &str| {
+        depends
+            .iter()
+            .find(|dep| dep["id"].as_str() == Some(dep_id))
+            .and_then(|dep| json_predicates(&dep["versions"]))
+    };
+
+    vec![JarMetadata {
+        platform: JarPlatform::Quilt,
+        name: id.to_owned(),
+        provides: vec![],
+        depends: vec![],
+        minecraft: versions("minecraft"),
+        loader: versions("quilt_loader"),
+    }]
+}
+
+fn parse_mods_toml(content: &str) -> Vec<JarMetadata> {
+    let Ok(table) = toml::from_str::<toml::Table>(content) else {
+        return vec![];
+    };
+
+    let dependencies = |mod_id: &str| {
+        table
+            .get("dependencies")
+            .and_then(|deps| deps.get(mod_id))
+            .and_then(toml::Value::as_array)
+            .cloned()
+            .unwrap_or_default()
+    };
+
+    table
+        .get("mods")
+        .and_then(toml::Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|m| m.get("modId").and_then(toml::Value::as_str))
+        .map(|mod_id| {
+            let deps = dependencies(mod_id);
+            let range = |dep_id: &str| {
+                deps.iter()
+                    .find(|dep| dep.get("modId").and_then(toml::Value::as_str) == Some(dep_id))
+                    .and_then(|dep| dep.get("versionRange"))
+                    .and_then(toml::Value::as_str)
+                    .map(|r| VersionRange::Maven(r.to_owned()))
+            };
+
+            let is_neoforge = range("neoforge").is_some();
+
+            JarMetadata {
+                platform: if is_neoforge {
+                    JarPlatform::NeoForge
+                } else {
+                    JarPlatform::Forge
+                },
+                name: mod_id.to_owned(),
+                provides: vec![],
+                depends: vec![],
+                minecraft: range("minecraft"),
+                loader: range(if is_neoforge { "neoforge" } else { "forge" }),
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,255 @@
+use std::{
+    cmp::Ordering,
+    fmt::{self, Display, Formatter},
+};
+
+/// A version requirement declared by a jar
+#[derive(Debug, Clone, PartialEq)]
+pub enum VersionRange {
+    /// Bukkit `api-version`, the oldest version the plugin supports
+    AtLeast(String),
+    /// Fabric and Quilt predicates like `>=1.20 <1.21` or `1.20.x`, any of them can match
+    Predicates(Vec<String>),
+    /// Maven version ranges from `mods.toml` like `[1.20,1.21)`
+    Maven(String),
+}
+
+impl VersionRange {
+    /// `None` if the versions can't be compared, for example snapshots
+    pub fn matches(&self, version: &str) -> Option<bool> {
+        let version = parse_version(version)?;
+
+        match self {
+            Self::AtLeast(min) => Some(compare(&version, &parse_version(min)?).is_ge()),
+            Self::Predicates(predicates) => {
+                let mut any_known = false;
+                for predicate in predicates {
+                    match predicate_matches(predicate, &version) {
+                        Some(true) => return Some(true),
+                        Some(false) => any_known = true,
+                        None => {}
+                    }
+                }
+                any_known.then_some(false)
+            }
+            Self::Maven(range) => maven_matches(range, &version),
+        }
+    }
+}
+
+impl Display for VersionRange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AtLeast(min) => write!(f, "{min}+"),
+            Self::Predicates(predicates) => f.write_str(&predicates.join(" or ")),
+            Self::Maven(range) => f.write_str(range),
+        }
+    }
+}
+
+/// Numeric parts of a version, ignoring `-pre` and `+build` suffixes
+fn parse_version(version: &str) -> Option<Vec<u64>> {
+    version
+        .trim()
+        .split(['-', '+'])
+        .next()?
+        .split('.')
+        .map(|part| part.parse().ok())
+        .collect()
+}
+
+/// Compare versions, missing parts count as 0
+fn compare(a: &[u64], b: &[u64]) -> Ordering {
+    (0..a.len().max(b.len()))
+        .map(|i| {
+            a.get(i)
+                .copied()
+                .unwrap_or(0)
+                .cmp(&b.get(i).copied().unwrap_or(0))
+        })
+        .find(|o| o.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
+
+/// Space separated comparators that all have to match
+fn predicate_matches(predicate: &str, version: &[u64]) -> Option<bool> {
+    for comparator in predicate.split_whitespace() {
+        if !comparator_matches(comparator, version)? {
+            return Some(false);
+        }
+    }
+
+    Some(true)
+}
+
+fn comparator_matches(comparator: &str, version: &[u64]) -> Option<bool> {
+    let (op, target) = [">=", "<=", ">", "<", "=", "~", "^"]
+        .into_iter()
+        .find_map(|op| comparator.strip_prefix(op).map(|rest| (op, rest)))
+        .unwrap_or(("", comparator));
+
+    if target == "*" {
+        return Some(true);
+    }
+
+    // 1.20.x matches every 1.20 version
+    if let Some(prefix) = target
+        .strip_suffix(".x")
+        .or_else(|| target.strip_suffix(".X"))
+        .or_else(|| target.strip_suffix(".*"))
+    {
+        let prefix = parse_version(prefix)?;
+        return Some(version.len() >= prefix.len() && version[..prefix.len()] == prefix[..]);
+    }
+
+    let target = parse_version(target)?;
+    let ordering = compare(version, &target);
+
+    Some(match op {
+        ">=" => ordering.is_ge(),
+        "<=" => ordering.is_le(),
+        ">" => ordering.is_gt(),
+        "<" => ordering.is_lt(),
+        // same minor version
+        "~" => ordering.is_ge() && version.get(..2) == target.get(..2),
+        // same major version
+        "^" => ordering.is_ge() && version.first() == target.first(),
+        _ => ordering.is_eq(),
+    })
+}
+
+/// `[1.20,1.21)`, `[1.20,)`, `(,1.21]`, `[1.20.1]` and lists of them.
+/// A bare version is only a recommendation and matches everything
+fn maven_matches(range: &str, version: &[u64]) -> Option<bool> {
+    let range = range.trim();
+    if range.is_empty() || !range.starts_with(['[', '(']) {
+        return Some(true);
+    }
+
+    let mut rest = range;
+    let mut any_known = false;
+
+    while let Some(start) = rest.find(['[', '(']) {
+        let end = start + rest[start..].find([']', ')'])?;
+        let inclusive_min = &rest[start..=start] == "[";
+        let inclusive_max = &rest[end..=end] == "]";
+        let bounds = &rest[start + 1..end];
+        rest = &rest[end + 1..];
+
+        let matches = if let Some((min, max)) = bounds.split_once(',') {
+            let min_ok = match min.trim() {
+                "" => Some(true),
+                min => parse_version(min).map(|min| {
+                    let o = compare(version, &min);
+                    o.is_gt() || (inclusive_min && o.is_eq())
+                }),
+            };
+            let max_ok = match max.trim() {
+                "" => Some(true),
+                max => parse_version(max).map(|max| {
+                    let o = compare(version, &max);
+                    o.is_lt() || (inclusive_max && o.is_eq())
+                }),
+            };
+
+            min_ok.zip(max_ok).map(|(a, b)| a && b)
+        } else {
+            parse_version(bounds).map(|exact| compare(version, &exact).is_eq())
+        };
+
+        match matches {
+            Some(true) => return Some(true),
+            Some(false) => any_known = true,
+            None => {}
+        }
+    }
+
+    any_known.then_some(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn predicates(predicates: &[&str]) -> VersionRange {
+        VersionRange::Predicates(predicates.iter().map(|p| (*p).to_owned()).collect())
+    }
+
+    #[test]
+    fn wildcards() {
+        let range = predicates(&["1.20.x"]);
+
+        assert_eq!(range.matches("1.20"), Some(true));
+        assert_eq!(range.matches("1.20.4"), Some(true));
+        assert_eq!(range.matches("1.21"), Some(false));
+        assert_eq!(range.matches("1.2"), Some(false));
+        assert_eq!(predicates(&["*"]).matches("1.8.9"), Some(true));
+    }
+
+    #[test]
+    fn comparators() {
+        let range = predicates(&[">=1.20 <1.20.5"]);
+        assert_eq!(range.matches("1.20.4"), Some(true));
+        assert_eq!(range.matches("1.20.5"), Some(false));
+
+        assert_eq!(
+            predicates(&["1.19.4", "1.20.1"]).matches("1.20.1"),
+            Some(true)
+        );
+        assert_eq!(predicates(&["=1.20.1"]).matches("1.20"), Some(false));
+    }
+
+    #[test]
+    fn tilde_and_caret() {
+        let tilde = predicates(&["~1.20.1"]);
+        assert_eq!(tilde.matches("1.20.4"), Some(true));
+        assert_eq!(tilde.matches("1.20"), Some(false));
+        assert_eq!(tilde.matches("1.21"), Some(false));
+
+        let caret = predicates(&["^1.20"]);
+        assert_eq!(caret.matches("1.21.1"), Some(true));
+        assert_eq!(caret.matches("1.19"), Some(false));
+        assert_eq!(caret.matches("2.0"), Some(false));
+    }
+
+    #[test]
+    fn maven_ranges() {
+        let range = VersionRange::Maven("[1.20,1.21)".to_owned());
+        assert_eq!(range.matches("1.20"), Some(true));
+        assert_eq!(range.matches("1.20.6"), Some(true));
+        assert_eq!(range.matches("1.21"), Some(false));
+        assert_eq!(range.matches("1.19.4"), Some(false));
+
+        let open = VersionRange::Maven("(,1.20.1]".to_owned());
+        assert_eq!(open.matches("1.20.1"), Some(true));
+        assert_eq!(open.matches("1.20.2"), Some(false));
+
+        let list = VersionRange::Maven("[1.19.2],[1.20,)".to_owned());
+        assert_eq!(list.matches("1.19.2"), Some(true));
+        assert_eq!(list.matches("1.19.3"), Some(false));
+        assert_eq!(list.matches("1.21"), Some(true));
+
+        // a bare version is only a recommendation
+        assert_eq!(
+            VersionRange::Maven("1.20.1".to_owned()).matches("1.19"),
+            Some(true)
+        );
+    }
+
+    #[test]
+    fn incomparable() {
+        assert_eq!(
+            VersionRange::AtLeast("1.20".to_owned()).matches("1.20.4"),
+            Some(true)
+        );
+        assert_eq!(
+            VersionRange::AtLeast("1.20".to_owned()).matches("1.19.4"),
+            Some(false)
+        );
+        assert_eq!(
+            VersionRange::AtLeast("1.20".to_owned()).matches("23w51a"),
+            None
+        );
+        assert_eq!(predicates(&[">=1.20-beta.1"]).matches("1.20.1"), Some(true));
+    }
+}
@@ -0,0 +1,326 @@
+/// Just enough YAML for plugin descriptors: block maps, block and flow lists,
+/// block scalars and plain or quoted scalars. Anchors, tags and flow maps aren't supported
+#[derive(Debug, Clone, PartialEq)]
+pub enum Yaml {
+    Scalar(String),
+    List(Vec<Yaml>),
+    Map(Vec<(String, Yaml)>),
+}
+
+impl Yaml {
+    pub fn parse(content: &str) -> Self {
+        let mut lines = content
+            .lines()
+            .map(strip_comment)
+            .filter(|line| !line.trim().is_empty() && line.trim() != "---")
+            .map(|line| {
+                let trimmed = line.trim_start();
+                (line.len() - trimmed.len(), trimmed.trim_end().to_owned())
+            })
+            .collect::<Vec<_>>();
+
+        let indent = lines.first().map_or(0, |(indent, _)| *indent);
+
+        parse_block(&mut lines, &mut 0, indent)
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Self> {
+        match self {
+            Self::Map(entries) => entries.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::Scalar(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Scalars of a list, a single scalar counts as a list of one
+    pub fn as_str_list(&self) -> Vec<String> {
+        match self {
+            Self::Scalar(s) if s.is_empty() => vec![],
+            Self::Scalar(s) => vec![s.clone()],
+            Self::List(items) => items
+                .iter()
+                .filter_map(Self::as_str)
+                .map(ToOwned::to_owned)
+                .collect(),
+            Self::Map(_) => vec![],
+        }
+    }
+
+    pub fn entries(&self) -> &[(String, Self)] {
+        match self {
+            Self::Map(entries) => entries,
+            _ => &[],
+        }
+    }
+}
+
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+    let mut prev = ' ';
+
+    for (i, c) in line.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), _) if q == c => quote = None,
+            (None, '#') if prev.is_whitespace() => return &line[..i],
+            _ => {}
+        }
+        prev = c;
+    }
+
+    line
+}
+
+fn unquote(value: &str) -> String {
+    let value = value.trim();
+
+    for q in ['"', '\''] {
+        if let Some(inner) = value.strip_prefix(q).and_then(|v| v.strip_suffix(q)) {
+            return inner.to_owned();
+        }
+    }
+
+    value.to_owned()
+}
+
+/// Split `key: value`, `None` for list items and plain scalars
+fn split_key(line: &str) -> Option<(String, &str)> {
+    if line.starts_with('-') || line.starts_with('[') {
+        return None;
+    }
+
+    let (key, value) = match line.strip_suffix(':') {
+        Some(key) if !key.contains(": ") => (key, ""),
+        _ => line.split_once(": ")?,
+    };
+
+    Some((unquote(key), value.trim()))
+}
+
+fn parse_flow_list(value: &str) -> Yaml {
+    let inner = value.trim().trim_start_matches('[').trim_end_matches(']');
+
+    Yaml::List(
+        inner
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(|item| Yaml::Scalar(unquote(item)))
+            .collect(),
+    )
+}
+
+fn parse_block(lines: &mut [(usize, String)], i: &mut usize, indent: usize) -> Yaml {
+    if lines
+        .get(*i)
+        .is_some_and(|(ind, line)| *ind == indent && (line == "-" || line.starts_with("- ")))
+    {
+        parse_list(lines, i, indent)
+    } else {
+        parse_map(lines, i, indent)
+    }
+}
+
+fn parse_list(lines: &mut [(usize, String)], i: &mut usize, indent: usize) -> Yaml {
+    let mut items = vec![];
+
+    while let Some((ind, line)) = lines.get(*i) {
+        if *ind != indent || !(line == "-" || line.starts_with("- ")) {
+            break;
+        }
+
+        let rest = line[1..].trim_start().to_owned();
+
+        if rest.is_empty() {
+            *i += 1;
+            match lines.get(*i) {
+                Some((child, _)) if *child > indent => {
+                    let child = *child;
+                    items.push(parse_block(lines, i, child));
+                }
+                _ => items.push(Yaml::Scalar(String::new())),
+            }
+        } else if split_key(&rest).is_some() {
+            // `- key: value` starts a map, continue it as if it was on its own line
+            let child = indent + (line.len() - rest.len());
+            lines[*i] = (child, rest);
+            items.push(parse_map(lines, i, child));
+        } else if rest.starts_with('[') {
+            items.push(parse_flow_list(&rest));
+            *i += 1;
+        } else {
+            items.push(Yaml::Scalar(unquote(&rest)));
+            *i += 1;
+        }
+    }
+
+    Yaml::List(items)
+}
+
+fn parse_map(lines: &mut [(usize, String)], i: &mut usize, indent: usize) -> Yaml {
+    let mut entries = vec![];
+
+    while let Some((ind, line)) = lines.get(*i) {
+        if *ind < indent {
+            break;
+        }
+
+        let Some((key, value)) = split_key(line).filter(|_| *ind == indent) else {
+            // something this parser doesn't understand, skip it
+            *i += 1;
+            continue;
+        };
+        let value = value.to_owned();
+        *i += 1;
+
+        let value = if value.is_empty() {
+            match lines.get(*i) {
+                Some((child, _)) if *child > indent => {
+                    let child = *child;
+                    parse_block(lines, i, child)
+                }
+                // lists are allowed on the same indentation as their key
+                Some((child, next)) if *child == indent && next.starts_with("- ") => {
+                    parse_list(lines, i, indent)
+                }
+                _ => Yaml::Scalar(String::new()),
+            }
+        } else if value.starts_with(['|', '>']) {
+            let mut text = vec![];
+            while let Some((child, line)) = lines.get(*i).filter(|(child, _)| *child > indent) {
+                text.push(format!("{}{line}", " ".repeat(child.saturating_sub(indent + 2))));
+                *i += 1;
+            }
+            Yaml::Scalar(text.join("\n"))
+        } else if value.starts_with('[') {
+            let mut flow = value;
+            while !flow.contains(']') {
+                let Some((_, line)) = lines.get(*i) else {
+                    break;
+                };
+                flow.push_str(line);
+                *i += 1;
+            }
+            parse_flow_list(&flow)
+        } else {
+            Yaml::Scalar(unquote(&value))
+        };
+
+        entries.push((key, value));
+    }
+
+    Yaml::Map(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scalar(s: &str) -> Yaml {
+        Yaml::Scalar(s.to_owned())
+    }
+
+    #[test]
+    fn plugin_yml() {
+        let yaml = Yaml::parse(
+            "name: Example # the name\n\
+            version: '1.0'\n\
+            main: com.example.Example\n\
+            api-version: \"1.20\"\n\
+            description: |\n  \
+              multiple\n  \
+              lines\n\
+            depend:\n\
+            - Vault\n\
+            - \"ProtocolLib\"\n\
+            softdepend: [PlaceholderAPI, 'LuckPerms']\n\
+            loadbefore: [\n  \
+              Essentials,\n  \
+              WorldEdit\n\
+            ]\n\
+            commands:\n  \
+              example:\n    \
+                aliases: [ex]\n",
+        );
+
+        assert_eq!(yaml.get("name"), Some(&scalar("Example")));
+        assert_eq!(yaml.get("version").and_then(Yaml::as_str), Some("1.0"));
+        assert_eq!(yaml.get("api-version").and_then(Yaml::as_str), Some("1.20"));
+        assert_eq!(
+            yaml.get("description").and_then(Yaml::as_str),
+            Some("multiple\nlines")
+        );
+        assert_eq!(
+            yaml.get("depend").unwrap().as_str_list(),
+            ["Vault", "ProtocolLib"]
+        );
+        assert_eq!(
+            yaml.get("softdepend").unwrap().as_str_list(),
+            ["PlaceholderAPI", "LuckPerms"]
+        );
+        assert_eq!(
+            yaml.get("loadbefore").unwrap().as_str_list(),
+            ["Essentials", "WorldEdit"]
+        );
+        assert_eq!(
+            yaml.get("commands")
+                .and_then(|c| c.get("example"))
+                .and_then(|c| c.get("aliases"))
+                .unwrap()
+                .as_str_list(),
+            ["ex"]
+        );
+    }
+
+    #[test]
+    fn indented_block_list() {
+        let yaml = Yaml::parse("depend:\n  - Vault\n  - LuckPerms\nmain: a.B\n");
+
+        assert_eq!(
+            yaml.get("depend").unwrap().as_str_list(),
+            ["Vault", "LuckPerms"]
+        );
+        assert_eq!(yaml.get("main").and_then(Yaml::as_str), Some("a.B"));
+    }
+
+    #[test]
+    fn list_of_maps() {
+        let yaml =
+            Yaml::parse("dependencies:\n  - name: Vault\n    required: true\n  - name: Foo\n");
+
+        assert_eq!(
+            yaml.get("dependencies"),
+            Some(&Yaml::List(vec![
+                Yaml::Map(vec![
+                    ("name".to_owned(), scalar("Vault")),
+                    ("required".to_owned(), scalar("true")),
+                ]),
+                Yaml::Map(vec![("name".to_owned(), scalar("Foo"))]),
+            ]))
+        );
+    }
+
+    #[test]
+    fn comments() {
+        assert_eq!(strip_comment("name: Example # comment"), "name: Example ");
+        assert_eq!(
+            strip_comment("prefix: \"#1 plugin\""),
+            "prefix: \"#1 plugin\""
+        );
+        assert_eq!(
+            strip_comment("prefix: '# not a comment'"),
+            "prefix: '# not a comment'"
+        );
+        assert_eq!(strip_comment("color: a#b"), "color: a#b");
+        assert_eq!(strip_comment("# only a comment"), "");
+
+        let yaml = Yaml::parse("# header\nprefix: \"#1\" # comment\n");
+        assert_eq!(yaml.get("prefix").and_then(Yaml::as_str), Some("#1"));
+    }
+}
@@ -1,13 +1,580 @@
-/*
-use std::path::PathBuf;
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashSet},
+    env,
+    fmt::{self, Display, Formatter},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
 
-use crate::app::App;
+use anyhow::{bail, Context, Result};
+use indicatif::ProgressBar;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use tokio::process::Command;
+use zip::ZipArchive;
 
-pub struct JavaVersion(pub PathBuf, pub String);
+use crate::{
+    app::{App, Cache, CacheStrategy, ResolvedFile},
+    model::{ServerType, SoftwareType},
+};
+
+pub const CACHE_DIR: &str = "java";
+/// `java -version` results of detected binaries, inside [`CACHE_DIR`]
+const INSPECT_CACHE: &str = "installations.json";
+const ADOPTIUM_API: &str = "https://api.adoptium.net/v3";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum JavaSource {
+    /// Installed by `mcman java install`
+    Managed,
+    /// From an environment variable like `JAVA_HOME` or `JAVA_17_BIN`
+    Env(String),
+    /// The `java` command in PATH
+    Path,
+    /// Found in a standard install location
+    System,
+}
+
+impl Display for JavaSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Managed => f.write_str("mcman"),
+            Self::Env(var) => write!(f, "${var}"),
+            Self::Path => f.write_str("PATH"),
+            Self::System => f.write_str("system"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct JavaInstallation {
+    /// Path to the java binary, or just `java` for the one in PATH
+    pub path: PathBuf,
+    /// Full version, like `17.0.9` or `1.8.0_392`
+    pub version: String,
+    pub major: u32,
+    pub source: JavaSource,
+}
+
+/// Parses the major version out of `1.8.0_392`, `17.0.9` or `22-ea`
+pub fn parse_major(version: &str) -> Option<u32> {
+    let mut parts = version.split(|c: char| !c.is_ascii_digit());
+
+    match parts.next()?.parse().ok()? {
+        1 => parts.next()?.parse().ok(),
+        major => Some(major),
+    }
+}
+
+/// Parses the output of `java -version`, which is printed to stderr
+///
+/// ```text
+/// openjdk version "17.0.9" 2023-10-17
+/// ```
+pub fn parse_version_output(output: &str) -> Option<String> {
+    let line = output.lines().find(|l| l.contains(" version \""))?;
+    let (_, rest) = line.split_once(" version \"")?;
+    let (version, _) = rest.split_once('"')?;
+
+    Some(version.to_owned())
+}
+
+fn installation(path: &Path, version: String, source: JavaSource) -> Option<JavaInstallation> {
+    Some(JavaInstallation {
+        path: path.to_path_buf(),
+        major: parse_major(&version)?,
+        version,
+        source,
+    })
+}
+
+/// The file a java binary points to, looking up bare names like `java` in PATH
+fn resolve_binary(path: &Path) -> Option<PathBuf> {
+    if path.components().count() > 1 {
+        return path.canonicalize().ok();
+    }
+
+    let name = if cfg!(windows) && path.extension().is_none() {
+        path.with_extension("exe")
+    } else {
+        path.to_path_buf()
+    };
+
+    env::split_paths(&env::var_os("PATH")?)
+        .map(|dir| dir.join(&name))
+        .find(|p| p.is_file())?
+        .canonicalize()
+        .ok()
+}
+
+/// Versions of already inspected binaries, so builds don't spawn every java again
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct InspectCache(BTreeMap<PathBuf, InspectedBinary>);
+
+#[derive(Debug, Serialize, Deserialize)]
+struct InspectedBinary {
+    /// Modification time of the binary when it was inspected
+    modified: SystemTime,
+    version: String,
+}
+
+fn java_bin(home: &Path) -> PathBuf {
+    home.join("bin").join(if cfg!(windows) { "java.exe" } else { "java" })
+}
+
+/// Directories that usually contain one folder per installed JDK/JRE
+fn system_locations() -> Vec<PathBuf> {
+    let mut locations = vec![];
+
+    match env::consts::OS {
+        "windows" => {
+            for var in ["ProgramFiles", "ProgramFiles(x86)"] {
+                if let Ok(program_files) = env::var(var) {
+                    let program_files = PathBuf::from(program_files);
+                    for vendor in [
+                        "Java",
+                        "Eclipse Adoptium",
+                        "Eclipse Foundation",
+                        "Zulu",
+                        "Microsoft",
+                        "Amazon Corretto",
+                        "BellSoft",
+                    ] {
+                        locations.push(program_files.join(vendor));
+                    }
+                }
+            }
+        }
+        "macos" => {
+            locations.push(PathBuf::from("/Library/Java/JavaVirtualMachines"));
+        }
+        _ => {
+            locations.extend(
+                ["/usr/lib/jvm", "/usr/java", "/opt/java", "/opt/jdk"].map(PathBuf::from),
+            );
+        }
+    }
+
+    if let Some(home) = dirs::home_dir() {
+        locations.push(home.join(".sdkman").join("candidates").join("java"));
+        locations.push(home.join(".jdks"));
+        if env::consts::OS == "macos" {
+            locations.push(home.join("Library").join("Java").join("JavaVirtualMachines"));
+        }
+    }
+
+    locations
+}
+
+/// Java homes inside `dir`, handling the `Contents/Home` layout of macOS
+fn homes_in(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = dir.read_dir() else {
+        return vec![];
+    };
+
+    entries
+        .filter_map(Result::ok)
+        .map(|e| e.path())
+        .map(|p| {
+            let mac_home = p.join("Contents").join("Home");
+            if mac_home.exists() {
+                mac_home
+            } else {
+                p
+            }
+        })
+        .filter(|p| java_bin(p).exists())
+        .collect()
+}
+
+/// Home of an installed managed runtime, archives extract to a `{release_name}` folder
+fn find_home(dir: &Path, release_name: &str) -> Option<PathBuf> {
+    homes_in(dir)
+        .into_iter()
+        .find(|h| h.starts_with(dir.join(release_name)))
+}
+
+#[derive(Debug, Deserialize)]
+struct AdoptiumAsset {
+    binary: AdoptiumBinary,
+    release_name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct AdoptiumBinary {
+    package: AdoptiumPackage,
+}
+
+#[derive(Debug, Deserialize)]
+struct AdoptiumPackage {
+    name: String,
+    link: String,
+    checksum: String,
+    size: u64,
+}
+
+/// Forge for Minecraft versions before this only runs on Java 8
+const FORGE_JAVA_8_BREAKOFF: Version = Version::new(1, 13, 0);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct JavaRequirement {
+    pub min: u32,
+    pub max: Option<u32>,
+}
+
+impl JavaRequirement {
+    pub fn matches(&self, major: u32) -> bool {
+        major >= self.min && self.max.map_or(true, |max| major <= max)
+    }
+}
+
+impl Display for JavaRequirement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.max {
+            Some(max) if max == self.min => write!(f, "Java {max}"),
+            Some(max) => write!(f, "Java {}-{max}", self.min),
+            None => write!(f, "Java {}+", self.min),
+        }
+    }
+}
 
 pub struct JavaAPI<'a>(pub &'a App);
 
-impl<'a> JavaAPI<'a> {
+impl JavaAPI<'_> {
+    /// Run `java -version` on a binary
+    pub async fn inspect(&self, path: &Path, source: JavaSource) -> Option<JavaInstallation> {
+        let output = Command::new(path).arg("-version").output().await.ok()?;
+        let version = parse_version_output(&String::from_utf8_lossy(&output.stderr))?;
+
+        installation(path, version, source)
+    }
+
+    /// Folder of the runtimes installed by `mcman java install`
+    pub fn managed_dir() -> Option<PathBuf> {
+        Cache::get_cache(CACHE_DIR).map(|c| c.0)
+    }
+
+    /// Find every java installation, in order of preference
+    pub async fn detect(&self) -> Vec<JavaInstallation> {
+        let mut candidates: Vec<(PathBuf, JavaSource)> = vec![];
+
+        if let Some(dir) = Self::managed_dir() {
+            candidates.extend(
+                homes_in(&dir)
+                    .iter()
+                    .map(|h| (java_bin(h), JavaSource::Managed)),
+            );
+        }
+
+        let mut vars = env::vars()
+            .filter(|(k, _)| k.starts_with("JAVA_") && (k.ends_with("_BIN") || k.ends_with("_HOME")))
+            .collect::<Vec<_>>();
+        vars.sort();
+
+        for (key, value) in vars {
+            let path = if key.ends_with("_HOME") {
+                java_bin(Path::new(&value))
+            } else {
+                PathBuf::from(value)
+            };
+            candidates.push((path, JavaSource::Env(key)));
+        }
+
+        candidates.push((PathBuf::from("java"), JavaSource::Path));
+
+        for dir in system_locations() {
+            candidates.extend(
+                homes_in(&dir)
+                    .iter()
+                    .map(|h| (java_bin(h), JavaSource::System)),
+            );
+        }
+
+        let cache = self.0.get_cache(CACHE_DIR);
+        let mut inspected = cache
+            .as_ref()
+            .and_then(|c| c.try_get_json::<InspectCache>(INSPECT_CACHE).ok().flatten())
+            .unwrap_or_default();
+        let mut changed = false;
+
+        let mut seen = HashSet::new();
+        let mut installations = vec![];
+
+        for (path, source) in candidates {
+            let resolved = resolve_binary(&path);
+            if !seen.insert(resolved.clone().unwrap_or(path.clone())) {
+                continue;
+            }
+
+            let modified = resolved
+                .as_ref()
+                .and_then(|p| p.metadata().ok()?.modified().ok());
+            let cached = resolved
+                .as_ref()
+                .zip(modified)
+                .and_then(|(p, modified)| inspected.0.get(p).filter(|e| e.modified == modified));
+
+            let java = match cached {
+                Some(entry) => installation(&path, entry.version.clone(), source),
+                None => {
+                    let java = self.inspect(&path, source).await;
+                    if let (Some(java), Some(resolved), Some(modified)) =
+                        (&java, resolved, modified)
+                    {
+                        let version = java.version.clone();
+                        inspected
+                            .0
+                            .insert(resolved, InspectedBinary { modified, version });
+                        changed = true;
+                    }
+                    java
+                }
+            };
+
+            installations.extend(java);
+        }
+
+        let before = inspected.0.len();
+        inspected.0.retain(|p, _| seen.contains(p));
+        changed |= inspected.0.len() != before;
+
+        if let Some(cache) = cache.filter(|_| changed) {
+            if let Err(e) = cache.write_json(INSPECT_CACHE, &inspected) {
+                self.0
+                    .dbg(format!("Couldn't cache the detected java versions: {e}"));
+            }
+        }
+
+        installations
+    }
+
+    /// The major java version the server needs, from the version info of its Minecraft version
+    ///
+    /// `None` for proxies, which don't have a Minecraft version
+    pub async fn required_major(&self) -> Result<Option<u32>> {
+        if self.0.server.jar.get_software_type() == SoftwareType::Proxy {
+            return Ok(None);
+        }
+
+        let id = match self.0.mc_version() {
+            "latest" => self.0.vanilla().fetch_latest_mcver().await?,
+            id => id.to_owned(),
+        };
+
+        let info = self.0.vanilla().fetch_version_info(&id).await?;
+
+        // very old versions don't have javaVersion
+        Ok(Some(match info.java_version.major_version {
+            0 => 8,
+            v => u32::from(v),
+        }))
+    }
+
+    /// The java versions the server can run on
+    ///
+    /// Old Forge versions only run on Java 8, newer ones follow the Minecraft version
+    pub async fn requirement(&self) -> Result<Option<JavaRequirement>> {
+        let Some(min) = self.required_major().await? else {
+            return Ok(None);
+        };
+
+        let max = match &self.0.server.jar {
+            ServerType::Forge { .. }
+                if Version::parse(self.0.mc_version()).is_ok_and(|v| v < FORGE_JAVA_8_BREAKOFF) =>
+            {
+                Some(8)
+            }
+            _ => None,
+        };
+
+        Ok(Some(JavaRequirement { min, max }))
+    }
+
+    /// Pick a java installation matching the requirement
+    ///
+    /// The `java` in PATH is preferred when it matches, otherwise the exact version, then the closest newer one
+    pub fn select<'a>(
+        installations: &'a [JavaInstallation],
+        requirement: &JavaRequirement,
+    ) -> Option<&'a JavaInstallation> {
+        installations
+            .iter()
+            .find(|j| j.source == JavaSource::Path && requirement.matches(j.major))
+            .or_else(|| installations.iter().find(|j| j.major == requirement.min))
+            .or_else(|| {
+                installations
+                    .iter()
+                    .filter(|j| requirement.matches(j.major))
+                    .min_by_key(|j| j.major)
+            })
+    }
+
+    /// The java binary to run the server with
+    ///
+    /// `JAVA_{v}_BIN` (with `launcher.java_version`) and `JAVA_BIN` always win,
+    /// otherwise it's picked from the detected installations using the requirement
+    ///
+    /// Only for running the server on this machine, start scripts use
+    /// [`ServerLauncher::get_java`](crate::model::ServerLauncher::get_java) instead
+    pub async fn get_java_for(&self, requirement: Option<&JavaRequirement>) -> String {
+        let launcher = &self.0.server.launcher;
+
+        let explicit = match &launcher.java_version {
+            Some(v) => env::var(format!("JAVA_{v}_BIN")).ok(),
+            None => env::var("JAVA_BIN").ok(),
+        };
+
+        if let Some(path) = explicit {
+            return path;
+        }
+
+        let requirement = match launcher.java_version.as_deref().and_then(parse_major) {
+            Some(major) => JavaRequirement {
+                min: major,
+                max: Some(major),
+            },
+            None => match requirement {
+                Some(requirement) => requirement.clone(),
+                None => return launcher.get_java(),
+            },
+        };
+
+        let installations = self.detect().await;
+
+        match Self::select(&installations, &requirement) {
+            Some(java) => java.path.to_string_lossy().into_owned(),
+            None => launcher.get_java(),
+        }
+    }
+
+    /// Fail early if `java` can't run the server, instead of letting the JVM crash
+    /// with an `UnsupportedClassVersionError`
+    pub async fn check(&self, java: &str, requirement: &JavaRequirement) -> Result<()> {
+        let Some(installation) = self.inspect(Path::new(java), JavaSource::Path).await else {
+            bail!(
+                "Couldn't run '{java} -version', is java installed?\n  \
+                - install {requirement} with `mcman java install {}`\n  \
+                - or set JAVA_BIN to a java binary",
+                requirement.min
+            );
+        };
+
+        if !requirement.matches(installation.major) {
+            bail!(
+                "{} needs {requirement}, but '{java}' is Java {}\n  \
+                - install it with `mcman java install {}`\n  \
+                - or set JAVA_BIN (or JAVA_{}_BIN with launcher.java_version) to a matching java\n  \
+                - set MCMAN_SKIP_JAVA_CHECK=true to start anyway",
+                self.0.server.name,
+                installation.version,
+                requirement.min,
+                requirement.min,
+            );
+        }
+
+        Ok(())
+    }
+
+    fn adoptium_os() -> &'static str {
+        match env::consts::OS {
+            "macos" => "mac",
+            os => os,
+        }
+    }
+
+    fn adoptium_arch() -> &'static str {
+        match env::consts::ARCH {
+            "x86_64" => "x64",
+            "x86" => "x32",
+            arch => arch,
+        }
+    }
+
+    /// Download a Temurin runtime into the cache, returns the installed java
+    pub async fn install(&self, major: u32) -> Result<JavaInstallation> {
+        if self.0.config.offline {
+            bail!("Can't install java in offline mode");
+        }
+
+        let dir = Self::managed_dir().context("No cache directory to install java into")?;
+
+        let assets: Vec<AdoptiumAsset> = self
+            .0
+            .http_client
+            .get(format!("{ADOPTIUM_API}/assets/latest/{major}/hotspot"))
+            .query(&[
+                ("architecture", Self::adoptium_arch()),
+                ("os", Self::adoptium_os()),
+                ("image_type", "jre"),
+                ("vendor", "eclipse"),
+            ])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        let asset = assets.into_iter().next().context(format!(
+            "No Temurin {major} runtime available for {}/{}",
+            Self::adoptium_os(),
+            Self::adoptium_arch()
+        ))?;
+
+        if let Some(home) = find_home(&dir, &asset.release_name) {
+            if let Some(java) = self.inspect(&java_bin(&home), JavaSource::Managed).await {
+                return Ok(java);
+            }
+        }
+
+        let package = asset.binary.package;
+        let resolved = ResolvedFile {
+            url: package.link,
+            filename: package.name.clone(),
+            cache: CacheStrategy::File {
+                namespace: Cow::Borrowed(CACHE_DIR),
+                path: format!("archives/{}", package.name),
+            },
+            size: Some(package.size),
+            hashes: BTreeMap::from([("sha256".to_owned(), package.checksum)]),
+        };
+
+        let tmp = tempfile::tempdir().context("Creating temporary directory")?;
+        self.0
+            .download_resolved(
+                resolved,
+                tmp.path().to_path_buf(),
+                self.0.multi_progress.add(ProgressBar::new_spinner()),
+            )
+            .await?;
+
+        let archive = tmp.path().join(&package.name);
+        std::fs::create_dir_all(&dir)?;
+
+        if package.name.ends_with(".zip") {
+            ZipArchive::new(std::fs::File::open(&archive)?)?
+                .extract(&dir)
+                .context("Extracting java archive")?;
+        } else {
+            let status = Command::new("tar")
+                .arg("-xzf")
+                .arg(&archive)
+                .arg("-C")
+                .arg(&dir)
+                .status()
+                .await
+                .context("Running tar to extract the java archive")?;
+
+            if !status.success() {
+                bail!("tar exited with {status} while extracting {}", package.name);
+            }
+        }
+
+        let home = find_home(&dir, &asset.release_name)
+            .context(format!("Couldn't find {} after extracting", asset.release_name))?;
 
+        self.inspect(&java_bin(&home), JavaSource::Managed)
+            .await
+            .context(format!("Installed java at {} doesn't work", home.display()))
+    }
 }
- */
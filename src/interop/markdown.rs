@@ -345,6 +345,7 @@ impl MarkdownAPI<'_> {
                 url,
                 filename,
                 desc,
+                ..
             } => (
                 format!(
                     "`{}`",
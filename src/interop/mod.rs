@@ -1,4 +1,5 @@
 pub mod hooks;
+pub mod jar;
 pub mod java;
 pub mod markdown;
 pub mod mrpack;
@@ -8,6 +8,7 @@ mod core;
 mod hot_reload;
 mod interop;
 mod model;
+mod protocol;
 mod sources;
 mod util;
 
@@ -35,10 +36,15 @@ enum Commands {
     Run(commands::run::RunArgs),
     /// Start a development session
     Dev(commands::dev::DevArgs),
+    /// Build, test or develop every server of a network
+    #[command(subcommand, visible_alias = "nw")]
+    Network(commands::network::Commands),
 
     /// Add a plugin/mod/datapack
     #[command(subcommand)]
     Add(commands::add::Commands),
+    /// Update pinned plugin/mod/datapack versions
+    Update(commands::update::Args),
     /// Pull files from server/ to config/
     Pull(commands::pull::Args),
     /// Helpers for setting up the environment
@@ -61,6 +67,17 @@ enum Commands {
     /// Download a downloadable
     #[command(visible_alias = "dl")]
     Download(commands::download::Args),
+    /// Download everything needed into the cache without building
+    Fetch(commands::fetch::Args),
+    /// Check server.toml and network.toml for problems without building
+    Check(commands::check::Args),
+    /// Show the status of a running server
+    Ping(commands::ping::Args),
+    /// Send a command to a running server over RCON
+    Rcon(commands::rcon::Args),
+    /// Find, install and select java runtimes
+    #[command(subcommand)]
+    Java(commands::java::Commands),
     /// Cache management commands
     #[command(subcommand)]
     Cache(commands::cache::Commands),
@@ -98,6 +115,13 @@ async fn main() -> Result<()> {
     match args.command {
         Commands::Init(args) => commands::init::run(base_app, args).await,
         Commands::Cache(subcommands) => commands::cache::run(subcommands),
+        Commands::Fetch(args) => commands::fetch::run(base_app, args).await,
+        Commands::Check(args) => commands::check::run(base_app, args).await,
+        Commands::Java(commands) => commands::java::run(base_app, commands).await,
+        Commands::Ping(args) => commands::ping::run(base_app, args).await,
+        Commands::Rcon(args) => commands::rcon::run(base_app, args).await,
+        Commands::Env(commands) => commands::env::run(base_app, commands).await,
+        Commands::Network(commands) => commands::network::run(base_app, commands).await,
         Commands::Version(args) => commands::version::run(base_app, args).await,
         c => {
             let mut app = base_app.upgrade()?;
@@ -112,10 +136,10 @@ async fn main() -> Result<()> {
                 Commands::Add(commands) => commands::add::run(app, commands).await,
                 Commands::Import(subcommands) => commands::import::run(app, subcommands).await,
                 Commands::Export(commands) => commands::export::run(app, commands).await,
+                Commands::Update(args) => commands::update::run(app, args).await,
                 Commands::Markdown => commands::markdown::run(app).await,
                 Commands::World(commands) => commands::world::run(&mut app, commands),
                 Commands::Pull(args) => commands::pull::run(&app, args),
-                Commands::Env(commands) => commands::env::run(&app, commands),
                 Commands::Eject => commands::eject::run(&app),
 
                 // Utils
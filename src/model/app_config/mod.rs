@@ -1,25 +1,25 @@
 use confique::Config;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize, Config)]
+#[derive(Debug, Clone, Serialize, Deserialize, Config)]
 pub struct MCLogsService {
     #[config(env = "upload_to_mclogs", default = false)]
     pub enabled: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize, Config)]
+#[derive(Debug, Clone, Serialize, Deserialize, Config)]
 pub struct Services {
     #[config(nested)]
     pub mclogs: MCLogsService,
 }
 
-#[derive(Debug, Serialize, Deserialize, Config)]
+#[derive(Debug, Clone, Serialize, Deserialize, Config)]
 pub struct Sources {
     #[config(nested)]
     pub github: GithubSource,
 }
 
-#[derive(Debug, Serialize, Deserialize, Config)]
+#[derive(Debug, Clone, Serialize, Deserialize, Config)]
 pub struct GithubSource {
     #[config(env = "GITHUB_TOKEN")]
     pub api_token: Option<String>,
@@ -27,7 +27,26 @@ pub struct GithubSource {
     pub api_url: String,
 }
 
-#[derive(Debug, Serialize, Deserialize, Config)]
+#[derive(Debug, Clone, Serialize, Deserialize, Config)]
+pub struct Downloads {
+    #[config(env = "MCMAN_PARALLEL_DOWNLOADS", default = 4)]
+    pub parallel: usize,
+    #[config(env = "MCMAN_DOWNLOAD_RETRIES", default = 3)]
+    pub retries: u32,
+    #[config(env = "MCMAN_VERIFY_HASHES", default = true)]
+    pub verify: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Config)]
+pub struct Secrets {
+    /// age identity (`AGE-SECRET-KEY-1...`) to decrypt `secrets.toml.age` with
+    #[config(env = "MCMAN_SECRETS_KEY")]
+    pub key: Option<String>,
+    #[config(env = "MCMAN_AGE_BIN", default = "age")]
+    pub age_bin: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Config)]
 pub struct AppConfig {
     #[config(default = [])]
     pub disable_cache: Vec<String>,
@@ -35,6 +54,14 @@ pub struct AppConfig {
     pub services: Services,
     #[config(nested)]
     pub sources: Sources,
+    #[config(nested)]
+    pub downloads: Downloads,
+    #[config(nested)]
+    pub secrets: Secrets,
     #[config(env = "JAVA_BIN", default = "java")]
     pub default_java: String,
+    #[config(env = "MCMAN_OFFLINE", default = false)]
+    pub offline: bool,
+    #[config(env = "MCMAN_SKIP_JAVA_CHECK", default = false)]
+    pub skip_java_check: bool,
 }
@@ -0,0 +1,63 @@
+use anyhow::Result;
+
+use crate::app::App;
+
+use super::Downloadable;
+
+/// A required dependency of an addon, as listed by its source
+#[derive(Debug, Clone)]
+pub struct Dependency {
+    /// Project title if the source has one
+    pub name: String,
+    /// Ids and slugs the project can be referred to with
+    pub ids: Vec<String>,
+    /// Entry to add to server.toml, `None` if it isn't on the same source
+    pub downloadable: Option<Downloadable>,
+}
+
+impl Dependency {
+    /// Shortcode to add the latest version with `mcman import url`
+    pub fn to_shortcode(&self) -> Option<String> {
+        match self.downloadable.as_ref()? {
+            Downloadable::Modrinth { id, .. } => Some(format!("mr:{id}")),
+            Downloadable::CurseForge { id, .. } => Some(format!("cf:{id}")),
+            Downloadable::Hangar { id, .. } => Some(format!("hangar:{id}")),
+            _ => None,
+        }
+    }
+
+    /// Check if an addon of the same source with one of `ids` is in the list
+    pub fn is_satisfied_by(&self, addons: &[Downloadable]) -> bool {
+        let matches = |id: &str| self.ids.iter().any(|i| i.eq_ignore_ascii_case(id));
+
+        addons.iter().any(|addon| match (&self.downloadable, addon) {
+            (Some(Downloadable::Modrinth { .. }), Downloadable::Modrinth { id, .. })
+            | (
+                Some(Downloadable::CurseForge { .. }),
+                Downloadable::CurseForge { id, .. } | Downloadable::CurseRinth { id, .. },
+            ) => matches(id),
+            // hangar ids can be `owner/slug`
+            (Some(Downloadable::Hangar { .. }) | None, Downloadable::Hangar { id, .. }) => {
+                matches(id.rsplit('/').next().unwrap_or(id))
+            }
+            (None, Downloadable::Spigot { id, .. }) => matches(id),
+            _ => false,
+        })
+    }
+}
+
+impl Downloadable {
+    /// Required dependencies, only Modrinth, Hangar and CurseForge list them
+    pub async fn required_dependencies(&self, app: &App) -> Result<Vec<Dependency>> {
+        match self {
+            Self::Modrinth { id, version } => {
+                app.modrinth().required_dependencies(id, version).await
+            }
+            Self::Hangar { id, version } => app.hangar().required_dependencies(id, version).await,
+            Self::CurseForge { id, version } => {
+                app.curseforge().required_dependencies(id, version).await
+            }
+            _ => Ok(vec![]),
+        }
+    }
+}
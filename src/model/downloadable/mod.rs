@@ -1,12 +1,16 @@
-use std::collections::BTreeMap;
+use std::{borrow::Cow, collections::BTreeMap};
 
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 
 use crate::app::{App, CacheStrategy, Resolvable, ResolvedFile};
 
+mod dependencies;
 mod markdown;
 mod meta;
+mod update;
+
+pub use dependencies::Dependency;
 
 #[derive(Debug, Deserialize, Serialize, Clone, Hash, PartialEq, Eq)]
 #[serde(tag = "type", rename_all = "lowercase")]
@@ -20,6 +24,9 @@ pub enum Downloadable {
         #[serde(default)]
         #[serde(skip_serializing_if = "crate::util::is_default")]
         desc: Option<String>,
+        #[serde(default)]
+        #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+        hashes: BTreeMap<String, String>,
     },
 
     #[serde(alias = "mr")]
@@ -97,7 +104,12 @@ pub fn artifact() -> String {
 impl Resolvable for Downloadable {
     async fn resolve_source(&self, app: &App) -> Result<ResolvedFile> {
         match self {
-            Self::Url { url, filename, .. } => Ok(ResolvedFile {
+            Self::Url {
+                url,
+                filename,
+                hashes,
+                ..
+            } => Ok(ResolvedFile {
                 url: url.clone(),
                 filename: if let Some(filename) = filename {
                     filename.clone()
@@ -105,9 +117,17 @@ impl Resolvable for Downloadable {
                     let url_clean = url.split('?').next().unwrap_or(url);
                     url_clean.split('/').next_back().unwrap().to_string()
                 },
-                cache: CacheStrategy::None,
+                // the content behind an url can change, only cache if we can check it
+                cache: if hashes.is_empty() {
+                    CacheStrategy::None
+                } else {
+                    CacheStrategy::Indexed {
+                        namespace: Cow::Borrowed("url"),
+                        key: url.clone(),
+                    }
+                },
                 size: None,
-                hashes: BTreeMap::new(),
+                hashes: hashes.clone(),
             }),
             Self::Modrinth { id, version } => app.modrinth().resolve_source(id, version).await,
             Self::CurseRinth { id, version } => app.curserinth().resolve_source(id, version).await,
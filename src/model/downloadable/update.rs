@@ -0,0 +1,124 @@
+use anyhow::{anyhow, Result};
+
+use crate::app::App;
+
+use super::Downloadable;
+
+impl Downloadable {
+    /// The version-like field of this downloadable, if it has one
+    pub fn get_version(&self) -> Option<&str> {
+        match self {
+            Self::Url { .. } => None,
+            Self::Modrinth { version, .. }
+            | Self::CurseRinth { version, .. }
+            | Self::CurseForge { version, .. }
+            | Self::Spigot { version, .. }
+            | Self::Hangar { version, .. }
+            | Self::Maven { version, .. } => Some(version),
+            Self::GithubRelease { tag, .. } => Some(tag),
+            Self::Jenkins { build, .. } => Some(build),
+        }
+    }
+
+    /// Returns a copy of this downloadable pinned to `new_version`
+    pub fn with_version(&self, new_version: &str) -> Self {
+        let mut dl = self.clone();
+
+        match &mut dl {
+            Self::Url { .. } => {}
+            Self::Modrinth { version, .. }
+            | Self::CurseRinth { version, .. }
+            | Self::CurseForge { version, .. }
+            | Self::Spigot { version, .. }
+            | Self::Hangar { version, .. }
+            | Self::Maven { version, .. } => new_version.clone_into(version),
+            Self::GithubRelease { tag, .. } => new_version.clone_into(tag),
+            Self::Jenkins { build, .. } => new_version.clone_into(build),
+        }
+
+        dl
+    }
+
+    /// Whether this is pinned to a fixed version that `mcman update` can bump
+    pub fn is_pinned(&self) -> bool {
+        self.get_version()
+            .is_some_and(|v| v != "latest" && !v.contains("${"))
+    }
+
+    /// Resolve the newest compatible version for this downloadable
+    ///
+    /// Returns the current version unchanged if it already points to the newest one
+    pub async fn fetch_latest_version(&self, app: &App) -> Result<Option<String>> {
+        let current = self.get_version().unwrap_or_default();
+
+        Ok(match self {
+            Self::Url { .. } => None,
+            Self::Modrinth { id, .. } => {
+                let all_versions = app.modrinth().fetch_all_versions(id).await?;
+                let versions = app.modrinth().filter_versions(&all_versions);
+                let latest = versions.first().ok_or(anyhow!(
+                    "No compatible versions for Modrinth project '{id}'"
+                ))?;
+
+                // keep the pin in the format server.toml uses, IDs aren't readable
+                let pinned = all_versions
+                    .iter()
+                    .find(|v| v.id == current || v.name == current || v.version_number == current);
+
+                Some(match pinned {
+                    Some(v) if v.id == current => latest.id.clone(),
+                    Some(v) if v.version_number != current => latest.name.clone(),
+                    _ => latest.version_number.clone(),
+                })
+            }
+            Self::CurseRinth { id, .. } => {
+                let (versions, _) = app.curserinth().fetch_versions(id).await?;
+                let latest = versions.first().ok_or(anyhow!(
+                    "No compatible versions for CurseRinth project '{id}'"
+                ))?;
+
+                Some(latest.id.clone())
+            }
+            Self::CurseForge { id, .. } => {
+                let versions = app.curseforge().fetch_versions(id).await?;
+                let latest = versions.first().ok_or(anyhow!(
+                    "No compatible versions for CurseForge project '{id}'"
+                ))?;
+
+                Some(latest.id.to_string())
+            }
+            Self::Spigot { id, .. } => Some(
+                app.spigot()
+                    .fetch_version(id, "latest")
+                    .await?
+                    .id
+                    .to_string(),
+            ),
+            Self::Hangar { id, .. } => Some(
+                app.hangar()
+                    .fetch_hangar_version(id, "latest")
+                    .await?
+                    .name,
+            ),
+            Self::GithubRelease { repo, asset, .. } => Some(
+                app.github()
+                    .fetch_latest_release(repo, asset)
+                    .await?
+                    .tag_name,
+            ),
+            Self::Jenkins { url, job, .. } => Some(
+                app.jenkins()
+                    .fetch_build(url, job, "latest")
+                    .await?
+                    .number
+                    .to_string(),
+            ),
+            Self::Maven {
+                url,
+                group,
+                artifact,
+                ..
+            } => Some(app.maven().fetch_versions(url, group, artifact).await?.0),
+        })
+    }
+}
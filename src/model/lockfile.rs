@@ -9,29 +9,56 @@ use std::{
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 
-use crate::app::ResolvedFile;
+use crate::app::{AddonType, ResolvedFile};
 
-use super::Downloadable;
+use super::{Downloadable, Server, ServerType};
 
-#[derive(Debug, Deserialize, Serialize)]
+/// Name of the lockfile that lives next to `server.toml` and is meant to be committed
+pub const COMMITTED_LOCKFILE: &str = "mcman.lock";
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
 #[serde(default)]
 pub struct Lockfile {
     #[serde(skip)]
     pub path: PathBuf,
 
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub server_jar: Option<LockedServerJar>,
+
     pub plugins: Vec<(Downloadable, ResolvedFile)>,
     pub mods: Vec<(Downloadable, ResolvedFile)>,
 
+    /// World downloads and datapacks
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub world_files: Vec<(Downloadable, ResolvedFile)>,
+
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
     pub server_vars: HashMap<String, String>,
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
     pub nw_vars: HashMap<String, String>,
 
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub files: Vec<BootstrappedFile>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct LockedServerJar {
+    pub mc_version: String,
+    pub jar: ServerType,
+    pub resolved: ResolvedFile,
+    /// Resolved loader version for quilt/forge/neoforge
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub loader_version: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct BootstrappedFile {
     pub path: PathBuf,
     pub date: SystemTime,
+    /// Keys of the variables used in the file, `secret:NAME` for secrets and
+    /// `env:NAME` for values from the environment or a `.env` file
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub variables: Vec<String>,
 }
 
 impl Lockfile {
@@ -46,6 +73,17 @@ impl Lockfile {
         }
     }
 
+    /// Load the committed lockfile next to `server.toml`, if there is one
+    pub fn get_committed(server_dir: &Path) -> Result<Option<Self>> {
+        let path = server_dir.join(COMMITTED_LOCKFILE);
+
+        if path.exists() {
+            Ok(Some(Self::load_from(&path)?))
+        } else {
+            Ok(None)
+        }
+    }
+
     pub fn load_from(path: &PathBuf) -> Result<Self> {
         let data = read_to_string(path)?;
         let mut nw: Self = serde_json::from_str(&data)?;
@@ -58,14 +96,56 @@ impl Lockfile {
 
         Ok(serde_json::to_writer_pretty(writer, &self)?)
     }
+
+    pub fn addons(&self, addon_type: AddonType) -> &Vec<(Downloadable, ResolvedFile)> {
+        match addon_type {
+            AddonType::Plugin => &self.plugins,
+            AddonType::Mod => &self.mods,
+        }
+    }
+
+    /// Whether the lock was made for the same minecraft version and server jar
+    ///
+    /// `latest` versions resolve differently for other versions/loaders, so nothing can be reused otherwise
+    pub fn matches_server(&self, server: &Server) -> bool {
+        self.server_jar
+            .as_ref()
+            .is_some_and(|s| s.mc_version == server.mc_version && s.jar == server.jar)
+    }
+
+    /// Every downloaded file recorded in this lockfile
+    pub fn resolved_files(&self) -> impl Iterator<Item = &ResolvedFile> {
+        self.server_jar
+            .iter()
+            .map(|s| &s.resolved)
+            .chain(self.plugins.iter().map(|(_, r)| r))
+            .chain(self.mods.iter().map(|(_, r)| r))
+            .chain(self.world_files.iter().map(|(_, r)| r))
+    }
+
+    pub fn find_addon(&self, addon_type: AddonType, dl: &Downloadable) -> Option<&ResolvedFile> {
+        self.addons(addon_type)
+            .iter()
+            .find(|(locked, _)| locked == dl)
+            .map(|(_, resolved)| resolved)
+    }
+
+    pub fn find_world_file(&self, dl: &Downloadable) -> Option<&ResolvedFile> {
+        self.world_files
+            .iter()
+            .find(|(locked, _)| locked == dl)
+            .map(|(_, resolved)| resolved)
+    }
 }
 
 impl Default for Lockfile {
     fn default() -> Self {
         Self {
             path: PathBuf::from("./.mcman.lock"),
+            server_jar: None,
             plugins: vec![],
             mods: vec![],
+            world_files: vec![],
             files: vec![],
             server_vars: HashMap::default(),
             nw_vars: HashMap::default(),
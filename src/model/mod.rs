@@ -7,6 +7,7 @@ mod network;
 mod serverlauncher;
 mod servertoml;
 mod servertype;
+mod smoke_test;
 mod world;
 
 pub use app_config::*;
@@ -18,4 +19,5 @@ pub use network::*;
 pub use serverlauncher::*;
 pub use servertoml::*;
 pub use servertype::*;
+pub use smoke_test::*;
 pub use world::*;
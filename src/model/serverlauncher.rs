@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 use std::{borrow::ToOwned, collections::HashMap, env};
 
+use crate::interop::java::JavaRequirement;
+
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Default)]
 #[serde(rename_all = "lowercase")]
 pub enum PresetFlags {
@@ -36,6 +38,8 @@ pub struct ServerLauncher {
     #[serde(skip_serializing_if = "crate::util::is_default")]
     pub disable: bool,
     #[serde(skip_serializing_if = "crate::util::is_default")]
+    pub systemd: bool,
+    #[serde(skip_serializing_if = "crate::util::is_default")]
     pub jvm_args: String,
     #[serde(skip_serializing_if = "crate::util::is_default")]
     pub game_args: String,
@@ -74,18 +78,62 @@ impl ServerLauncher {
         }
     }
 
-    pub fn generate_script_linux(&self, _servername: &str, startup: &StartupMethod) -> String {
+    pub fn generate_script_linux(
+        &self,
+        java: &str,
+        requirement: Option<&JavaRequirement>,
+        startup: &StartupMethod,
+    ) -> String {
+        let check = requirement.map_or(String::new(), |req| {
+            let max = req.max.map_or(String::new(), |max| {
+                format!(" || [ \"$JAVA_MAJOR\" -gt {max} ]")
+            });
+
+            format!(
+                "JAVA_MAJOR=$(\"{java}\" -version 2>&1 | sed -n 's/.* version \"\\(1\\.\\)\\{{0,1\\}}\\([0-9]*\\).*/\\2/p' | head -n 1)\n\
+                if [ -n \"$JAVA_MAJOR\" ] && {{ [ \"$JAVA_MAJOR\" -lt {}{max} ]; }}; then\n\
+                \x20   echo \"This server needs {req}, but {java} is Java $JAVA_MAJOR\" >&2\n\
+                \x20   exit 1\n\
+                fi\n",
+                req.min
+            )
+        });
+
         format!(
-            "#!/bin/sh\n# generated by mcman\n{} {} \"$@\"\n",
-            self.get_java(),
+            "#!/bin/sh\n# generated by mcman\n{check}\"{java}\" {} \"$@\"\n",
             self.get_arguments(startup, "linux").join(" ")
         )
     }
 
-    pub fn generate_script_win(&self, servername: &str, startup: &StartupMethod) -> String {
+    pub fn generate_script_win(
+        &self,
+        java: &str,
+        requirement: Option<&JavaRequirement>,
+        servername: &str,
+        startup: &StartupMethod,
+    ) -> String {
+        let check = requirement.map_or(String::new(), |req| {
+            let max = req.max.map_or(String::new(), |max| {
+                format!("if %JAVA_MAJOR% GTR {max} set JAVA_WRONG=1\r\n")
+            });
+
+            format!(
+                "for /f \"tokens=3\" %%v in ('call \"{java}\" -version 2^>^&1 ^| findstr /i \"version\"') do set JAVA_VERSION=%%~v\r\n\
+                for /f \"delims=.-_ tokens=1,2\" %%a in (\"%JAVA_VERSION%\") do if \"%%a\"==\"1\" (set JAVA_MAJOR=%%b) else (set JAVA_MAJOR=%%a)\r\n\
+                if defined JAVA_MAJOR (\r\n\
+                \x20   if %JAVA_MAJOR% LSS {} set JAVA_WRONG=1\r\n\
+                \x20   {max}\
+                )\r\n\
+                if defined JAVA_WRONG (\r\n\
+                \x20   echo This server needs {req}, but \"{java}\" is Java %JAVA_MAJOR%\r\n\
+                \x20   exit /b 1\r\n\
+                )\r\n",
+                req.min
+            )
+        });
+
         format!(
-            "@echo off\r\n:: generated by mcman\r\ntitle {servername}\r\n{} {} %*\r\n",
-            self.get_java(),
+            "@echo off\r\n:: generated by mcman\r\ntitle {servername}\r\n{check}\"{java}\" {} %*\r\n",
             self.get_arguments(startup, "windows").join(" ")
         )
     }
@@ -147,6 +195,7 @@ impl Default for ServerLauncher {
             jvm_args: String::new(),
             game_args: String::new(),
             disable: false,
+            systemd: false,
             eula_args: true,
             memory: String::new(),
             properties: HashMap::default(),
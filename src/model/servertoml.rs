@@ -11,7 +11,9 @@ use serde::{Deserialize, Serialize};
 
 use crate::util::dollar_repl;
 
-use super::{ClientSideMod, Downloadable, Hook, ServerLauncher, ServerType, World};
+use super::{
+    ClientSideMod, Downloadable, Hook, ServerLauncher, ServerType, SmokeTestOptions, World,
+};
 
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
 #[serde(default)]
@@ -59,6 +61,10 @@ pub struct Server {
     #[serde(default)]
     pub options: ServerOptions,
 
+    #[serde(default)]
+    #[serde(skip_serializing_if = "SmokeTestOptions::is_empty")]
+    pub tests: SmokeTestOptions,
+
     #[serde(skip_serializing_if = "HashMap::is_empty")]
     pub worlds: HashMap<String, World>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
@@ -210,6 +216,7 @@ impl Default for Server {
             markdown: MarkdownOptions::default(),
             hooks: HashMap::new(),
             options: ServerOptions::default(),
+            tests: SmokeTestOptions::default(),
             worlds: HashMap::new(),
             plugins: vec![],
             mods: vec![],
@@ -0,0 +1,48 @@
+use serde::{Deserialize, Serialize};
+
+/// `[tests]` in server.toml, checks that `mcman run --test` runs after the server started
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct SmokeTestOptions {
+    /// Default timeout of checks in seconds
+    pub timeout: u64,
+    /// Also consider the server started once it answers a server list ping
+    pub ping: bool,
+    pub checks: Vec<SmokeTestCheck>,
+}
+
+impl Default for SmokeTestOptions {
+    fn default() -> Self {
+        Self {
+            timeout: 30,
+            ping: false,
+            checks: vec![],
+        }
+    }
+}
+
+impl SmokeTestOptions {
+    pub fn is_empty(&self) -> bool {
+        self.checks.is_empty() && !self.ping
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SmokeTestCheck {
+    pub name: Option<String>,
+    /// Console command to send before waiting for `expect`
+    pub command: Option<String>,
+    /// Regex that a log line needs to match
+    pub expect: String,
+    /// Timeout in seconds, defaults to `tests.timeout`
+    pub timeout: Option<u64>,
+}
+
+impl SmokeTestCheck {
+    pub fn get_name(&self) -> &str {
+        self.name
+            .as_deref()
+            .or(self.command.as_deref())
+            .unwrap_or(&self.expect)
+    }
+}
@@ -0,0 +1,182 @@
+use std::{collections::HashMap, path::Path};
+
+use anyhow::{bail, Context, Result};
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use crate::{app::App, model::SoftwareType};
+
+pub mod rcon;
+pub mod slp;
+
+pub const DEFAULT_PORT: u16 = 25565;
+pub const DEFAULT_PROXY_PORT: u16 = 25577;
+pub const DEFAULT_RCON_PORT: u16 = 25575;
+
+/// Parse a `.properties` file, ignoring comments and escapes
+pub fn parse_properties(content: &str) -> HashMap<String, String> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#') && !line.starts_with('!'))
+        .filter_map(|line| line.split_once(['=', ':']))
+        .map(|(k, v)| (k.trim().to_owned(), v.trim().to_owned()))
+        .collect()
+}
+
+pub fn read_properties(path: &Path) -> Result<HashMap<String, String>> {
+    let content = std::fs::read_to_string(path).context(format!("Reading {}", path.display()))?;
+
+    Ok(parse_properties(&content))
+}
+
+/// Split `host[:port]`, `[::1]:port` is allowed for IPv6
+pub fn parse_address(address: &str, default_port: u16) -> Result<(String, u16)> {
+    let (host, port) = if let Some(rest) = address.strip_prefix('[') {
+        let (host, rest) = rest.split_once(']').context("Missing ']' in address")?;
+        (host, rest.strip_prefix(':'))
+    } else {
+        match address.rsplit_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (address, None),
+        }
+    };
+
+    let port = match port {
+        Some(port) => port.parse().context(format!("Invalid port '{port}'"))?,
+        None => default_port,
+    };
+
+    if host.is_empty() {
+        bail!("Missing host in address '{address}'");
+    }
+
+    Ok((host.to_owned(), port))
+}
+
+/// Address of the built server, from network.toml, `PORT_<name>` or `server-port`
+pub fn server_address(app: &App, output_dir: &Path) -> (String, u16) {
+    let port = app
+        .var("SERVER_PORT")
+        .and_then(|p| p.parse().ok())
+        .or_else(|| {
+            read_properties(&output_dir.join("server.properties"))
+                .ok()?
+                .get("server-port")?
+                .parse()
+                .ok()
+        })
+        .unwrap_or(
+            if app.server.jar.get_software_type() == SoftwareType::Proxy {
+                DEFAULT_PROXY_PORT
+            } else {
+                DEFAULT_PORT
+            },
+        );
+
+    ("127.0.0.1".to_owned(), port)
+}
+
+/// `rcon.port` and `rcon.password` from server.properties
+pub fn rcon_settings(props: &HashMap<String, String>) -> Result<(u16, Option<String>)> {
+    if props.get("enable-rcon").map(String::as_str) != Some("true") {
+        bail!("RCON isn't enabled, set enable-rcon=true in config/server.properties");
+    }
+
+    let port = props
+        .get("rcon.port")
+        .and_then(|p| p.parse().ok())
+        .unwrap_or(DEFAULT_RCON_PORT);
+    let password = props
+        .get("rcon.password")
+        .filter(|p| !p.is_empty())
+        .cloned();
+
+    Ok((port, password))
+}
+
+/// Minecraft packets are prefixed with their length as a `VarInt`
+pub fn write_varint(buf: &mut Vec<u8>, value: i32) {
+    #[allow(clippy::cast_sign_loss)]
+    let mut value = value as u32;
+
+    loop {
+        #[allow(clippy::cast_possible_truncation)]
+        let byte = (value & 0x7F) as u8;
+        value >>= 7;
+
+        if value == 0 {
+            buf.push(byte);
+            break;
+        }
+
+        buf.push(byte | 0x80);
+    }
+}
+
+pub async fn read_varint<R: AsyncRead + Unpin>(reader: &mut R) -> Result<i32> {
+    let mut value = 0_u32;
+
+    for i in 0..5 {
+        let byte = reader.read_u8().await?;
+        value |= u32::from(byte & 0x7F) << (7 * i);
+
+        if byte & 0x80 == 0 {
+            #[allow(clippy::cast_possible_wrap)]
+            return Ok(value as i32);
+        }
+    }
+
+    bail!("VarInt is too big")
+}
+
+pub fn write_string(buf: &mut Vec<u8>, s: &str) {
+    #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+    write_varint(buf, s.len() as i32);
+    buf.extend_from_slice(s.as_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn roundtrip(value: i32) -> Vec<u8> {
+        let mut buf = vec![];
+        write_varint(&mut buf, value);
+
+        assert_eq!(read_varint(&mut buf.as_slice()).await.unwrap(), value);
+
+        buf
+    }
+
+    #[tokio::test]
+    async fn varint_roundtrip() {
+        for (value, len) in [
+            (0, 1),
+            (1, 1),
+            (127, 1),
+            (128, 2),
+            (25565, 3),
+            (i32::MAX, 5),
+        ] {
+            assert_eq!(roundtrip(value).await.len(), len, "length of {value}");
+        }
+    }
+
+    #[tokio::test]
+    async fn varint_negative() {
+        assert_eq!(roundtrip(-1).await, [0xff, 0xff, 0xff, 0xff, 0x0f]);
+        assert_eq!(roundtrip(i32::MIN).await, [0x80, 0x80, 0x80, 0x80, 0x08]);
+    }
+
+    #[tokio::test]
+    async fn varint_overflow() {
+        // the 5th byte still has the continuation bit set
+        let bytes = [0xff, 0xff, 0xff, 0xff, 0xff, 0x01];
+        assert!(read_varint(&mut &bytes[..]).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn varint_cut_off() {
+        assert!(read_varint(&mut &[0x80][..]).await.is_err());
+    }
+}
@@ -0,0 +1,203 @@
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    net::TcpStream,
+};
+
+const TYPE_RESPONSE: i32 = 0;
+const TYPE_COMMAND: i32 = 2;
+const TYPE_AUTH_RESPONSE: i32 = 2;
+const TYPE_LOGIN: i32 = 3;
+
+/// Vanilla drops commands longer than this
+const MAX_PAYLOAD_SIZE: usize = 1446;
+/// Responses are split into packets of 4096 bytes, the rest are some sanity limits
+const MAX_PACKET_SIZE: i32 = 1 << 16;
+
+struct Packet {
+    id: i32,
+    ty: i32,
+    body: String,
+}
+
+/// Remote console, enabled with `enable-rcon` in server.properties
+///
+/// See <https://wiki.vg/RCON>
+pub struct RconClient<S> {
+    stream: S,
+    next_id: i32,
+}
+
+impl RconClient<TcpStream> {
+    pub async fn connect(host: &str, port: u16, password: &str, timeout: Duration) -> Result<Self> {
+        tokio::time::timeout(timeout, async {
+            let stream = TcpStream::connect((host, port))
+                .await
+                .context(format!("Connecting to {host}:{port}"))?;
+
+            Self::login(stream, password).await
+        })
+        .await
+        .context(format!("Timed out connecting to {host}:{port}"))?
+    }
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> RconClient<S> {
+    pub async fn login(stream: S, password: &str) -> Result<Self> {
+        let mut client = Self { stream, next_id: 1 };
+
+        let id = client.next_id();
+        client.send(id, TYPE_LOGIN, password).await?;
+
+        // some implementations send an empty response before the auth response
+        loop {
+            let packet = client.recv().await.context("Reading login response")?;
+
+            if packet.id == -1 {
+                bail!("RCON login failed, check rcon.password");
+            }
+
+            if packet.ty == TYPE_AUTH_RESPONSE && packet.id == id {
+                break;
+            }
+        }
+
+        Ok(client)
+    }
+
+    /// Run a command and return its output
+    pub async fn command(&mut self, command: &str) -> Result<String> {
+        if command.len() > MAX_PAYLOAD_SIZE {
+            bail!("Command is longer than {MAX_PAYLOAD_SIZE} bytes");
+        }
+
+        let id = self.next_id();
+        self.send(id, TYPE_COMMAND, command).await?;
+
+        // long outputs are split into multiple packets without telling where they end,
+        // but the answer to an invalid packet comes after all of them
+        let end_id = self.next_id();
+        self.send(end_id, TYPE_RESPONSE, "").await?;
+
+        let mut output = String::new();
+        loop {
+            let packet = self.recv().await.context("Reading command response")?;
+
+            if packet.id == end_id {
+                break;
+            }
+
+            if packet.id == id {
+                output.push_str(&packet.body);
+            }
+        }
+
+        Ok(output)
+    }
+
+    fn next_id(&mut self) -> i32 {
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1).max(1);
+        id
+    }
+
+    async fn send(&mut self, id: i32, ty: i32, body: &str) -> Result<()> {
+        let mut packet = vec![];
+        packet.extend_from_slice(&i32::try_from(body.len() + 10)?.to_le_bytes());
+        packet.extend_from_slice(&id.to_le_bytes());
+        packet.extend_from_slice(&ty.to_le_bytes());
+        packet.extend_from_slice(body.as_bytes());
+        packet.extend_from_slice(&[0, 0]);
+
+        self.stream.write_all(&packet).await?;
+        self.stream.flush().await?;
+
+        Ok(())
+    }
+
+    async fn recv(&mut self) -> Result<Packet> {
+        let len = self.stream.read_i32_le().await?;
+
+        if !(10..=MAX_PACKET_SIZE).contains(&len) {
+            bail!("Invalid packet length {len}");
+        }
+
+        let mut data = vec![0; usize::try_from(len)?];
+        self.stream.read_exact(&mut data).await?;
+
+        let id = i32::from_le_bytes(data[0..4].try_into()?);
+        let ty = i32::from_le_bytes(data[4..8].try_into()?);
+        let body = String::from_utf8_lossy(&data[8..data.len() - 2]).into_owned();
+
+        Ok(Packet { id, ty, body })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::{duplex, DuplexStream};
+
+    use super::*;
+
+    /// The server side uses the same packet format, so the client methods are reused
+    fn fake_server(stream: DuplexStream) -> RconClient<DuplexStream> {
+        RconClient { stream, next_id: 1 }
+    }
+
+    #[tokio::test]
+    async fn login_failed() {
+        let (client, server) = duplex(4096);
+
+        let server = tokio::spawn(async move {
+            let mut server = fake_server(server);
+            let login = server.recv().await.unwrap();
+            assert_eq!((login.ty, login.body.as_str()), (TYPE_LOGIN, "hunter2"));
+
+            server.send(login.id, TYPE_RESPONSE, "").await.unwrap();
+            server.send(-1, TYPE_AUTH_RESPONSE, "").await.unwrap();
+        });
+
+        let Err(e) = RconClient::login(client, "hunter2").await else {
+            panic!("login should fail");
+        };
+        assert!(e.to_string().contains("login failed"), "{e}");
+
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn multi_packet_response() {
+        let (client, server) = duplex(4096);
+        let parts = [
+            "There are 2 of a max of 20 players online: ",
+            "Steve, ",
+            "Alex",
+        ];
+
+        let server = tokio::spawn(async move {
+            let mut server = fake_server(server);
+            let login = server.recv().await.unwrap();
+            server.send(login.id, TYPE_AUTH_RESPONSE, "").await.unwrap();
+
+            let command = server.recv().await.unwrap();
+            assert_eq!((command.ty, command.body.as_str()), (TYPE_COMMAND, "list"));
+            let end = server.recv().await.unwrap();
+            assert_eq!(end.ty, TYPE_RESPONSE);
+
+            for part in parts {
+                server.send(command.id, TYPE_RESPONSE, part).await.unwrap();
+            }
+            server
+                .send(end.id, TYPE_RESPONSE, "Unknown request 0")
+                .await
+                .unwrap();
+        });
+
+        let mut client = RconClient::login(client, "hunter2").await.unwrap();
+        assert_eq!(client.command("list").await.unwrap(), parts.concat());
+
+        server.await.unwrap();
+    }
+}
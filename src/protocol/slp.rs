@@ -0,0 +1,267 @@
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use serde_json::Value;
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    net::TcpStream,
+};
+
+use super::{read_varint, write_string, write_varint};
+
+/// Status responses with a favicon are a few dozen KiB
+const MAX_PACKET_SIZE: i32 = 1 << 21;
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct Status {
+    pub version: StatusVersion,
+    pub players: Option<StatusPlayers>,
+    #[serde(default)]
+    pub description: Value,
+    pub favicon: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct StatusVersion {
+    pub name: String,
+    pub protocol: i32,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct StatusPlayers {
+    pub max: i64,
+    pub online: i64,
+    #[serde(default)]
+    pub sample: Vec<PlayerSample>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct PlayerSample {
+    pub name: String,
+    pub id: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct PingResult {
+    pub status: Status,
+    /// The status as the server sent it
+    pub raw: String,
+    pub latency: Duration,
+}
+
+impl Status {
+    /// The MOTD without formatting
+    pub fn motd(&self) -> String {
+        let mut text = String::new();
+        flatten_text(&self.description, &mut text);
+
+        // legacy formatting codes
+        let mut motd = String::new();
+        let mut chars = text.chars();
+        while let Some(c) = chars.next() {
+            if c == '§' {
+                chars.next();
+            } else {
+                motd.push(c);
+            }
+        }
+
+        motd
+    }
+}
+
+/// Text of a chat component
+fn flatten_text(component: &Value, out: &mut String) {
+    match component {
+        Value::String(s) => out.push_str(s),
+        Value::Array(list) => list.iter().for_each(|c| flatten_text(c, out)),
+        Value::Object(map) => {
+            if let Some(Value::String(text)) = map.get("text") {
+                out.push_str(text);
+            }
+            if let Some(extra) = map.get("extra") {
+                flatten_text(extra, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Server List Ping, what the multiplayer menu uses to show the status of a server
+///
+/// `timeout` applies to the whole exchange, see <https://wiki.vg/Server_List_Ping>
+pub async fn ping(host: &str, port: u16, timeout: Duration) -> Result<PingResult> {
+    tokio::time::timeout(timeout, async {
+        let mut stream = TcpStream::connect((host, port))
+            .await
+            .context(format!("Connecting to {host}:{port}"))?;
+
+        ping_stream(&mut stream, host, port).await
+    })
+    .await
+    .context(format!("Timed out pinging {host}:{port}"))?
+}
+
+pub async fn ping_stream<S: AsyncRead + AsyncWrite + Unpin>(
+    stream: &mut S,
+    host: &str,
+    port: u16,
+) -> Result<PingResult> {
+    // handshake, protocol version -1 since we don't know it yet
+    let mut handshake = vec![];
+    write_varint(&mut handshake, 0x00);
+    write_varint(&mut handshake, -1);
+    write_string(&mut handshake, host);
+    handshake.extend_from_slice(&port.to_be_bytes());
+    write_varint(&mut handshake, 1);
+    write_packet(stream, &handshake).await?;
+
+    // status request
+    let started = Instant::now();
+    write_packet(stream, &[0x00]).await?;
+
+    let packet = read_packet(stream)
+        .await
+        .context("Reading status response")?;
+    let mut reader = packet.as_slice();
+    if read_varint(&mut reader).await? != 0x00 {
+        bail!("Expected a status response");
+    }
+
+    let len = read_varint(&mut reader).await?;
+    let raw = String::from_utf8(
+        reader
+            .get(..usize::try_from(len)?)
+            .context("Status response is cut off")?
+            .to_vec(),
+    )?;
+    let status: Status = serde_json::from_str(&raw).context("Parsing status response")?;
+    let status_latency = started.elapsed();
+
+    // some servers close the connection after the status instead of answering the ping
+    let latency = ping_pong(stream).await.unwrap_or(status_latency);
+
+    Ok(PingResult {
+        status,
+        raw,
+        latency,
+    })
+}
+
+async fn ping_pong<S: AsyncRead + AsyncWrite + Unpin>(stream: &mut S) -> Result<Duration> {
+    let payload = i64::from(std::process::id());
+
+    let mut request = vec![];
+    write_varint(&mut request, 0x01);
+    request.extend_from_slice(&payload.to_be_bytes());
+
+    let started = Instant::now();
+    write_packet(stream, &request).await?;
+    let response = read_packet(stream).await?;
+
+    if response.get(1..) != Some(&payload.to_be_bytes()[..]) {
+        bail!("Unexpected pong");
+    }
+
+    Ok(started.elapsed())
+}
+
+async fn write_packet<S: AsyncWrite + Unpin>(stream: &mut S, data: &[u8]) -> Result<()> {
+    let mut packet = vec![];
+    write_varint(&mut packet, i32::try_from(data.len())?);
+    packet.extend_from_slice(data);
+
+    stream.write_all(&packet).await?;
+    stream.flush().await?;
+
+    Ok(())
+}
+
+async fn read_packet<S: AsyncRead + Unpin>(stream: &mut S) -> Result<Vec<u8>> {
+    let len = read_varint(stream).await?;
+
+    if !(1..=MAX_PACKET_SIZE).contains(&len) {
+        bail!("Invalid packet length {len}");
+    }
+
+    let mut packet = vec![0; usize::try_from(len)?];
+    stream.read_exact(&mut packet).await?;
+
+    Ok(packet)
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::duplex;
+
+    use super::*;
+
+    const STATUS: &str = r#"{"version":{"name":"1.20.4","protocol":765},"players":{"max":20,"online":1,"sample":[{"name":"Steve","id":"8667ba71-b85a-4004-af54-457a9734eed7"}]},"description":{"text":"§aHello ","extra":[{"text":"world"}]}}"#;
+
+    /// Answer the handshake and the status request, then the ping if `pong` is set.
+    /// The connection is closed when it returns
+    async fn fake_server<S: AsyncRead + AsyncWrite + Unpin>(mut stream: S, pong: bool) {
+        let handshake = read_packet(&mut stream).await.unwrap();
+        let mut reader = handshake.as_slice();
+        assert_eq!(read_varint(&mut reader).await.unwrap(), 0x00);
+        assert_eq!(read_varint(&mut reader).await.unwrap(), -1);
+        assert_eq!(read_varint(&mut reader).await.unwrap(), 9);
+        assert_eq!(&reader[..9], b"localhost");
+        assert_eq!(reader[9..], [0x63, 0xdd, 0x01]);
+
+        assert_eq!(read_packet(&mut stream).await.unwrap(), [0x00]);
+
+        let mut response = vec![];
+        write_varint(&mut response, 0x00);
+        write_string(&mut response, STATUS);
+        write_packet(&mut stream, &response).await.unwrap();
+
+        if pong {
+            let request = read_packet(&mut stream).await.unwrap();
+            assert_eq!(request[0], 0x01);
+            write_packet(&mut stream, &request).await.unwrap();
+        }
+    }
+
+    async fn ping_fake_server(pong: bool) -> PingResult {
+        let (mut client, server) = duplex(4096);
+        let server = tokio::spawn(fake_server(server, pong));
+
+        let result = ping_stream(&mut client, "localhost", 25565).await.unwrap();
+        server.await.unwrap();
+
+        result
+    }
+
+    #[tokio::test]
+    async fn status_and_pong() {
+        let result = ping_fake_server(true).await;
+
+        assert_eq!(result.raw, STATUS);
+        assert_eq!(result.status.version.name, "1.20.4");
+        assert_eq!(result.status.version.protocol, 765);
+        assert_eq!(result.status.motd(), "Hello world");
+
+        let players = result.status.players.unwrap();
+        assert_eq!((players.online, players.max), (1, 20));
+        assert_eq!(players.sample[0].name, "Steve");
+    }
+
+    #[tokio::test]
+    async fn closed_after_status() {
+        let result = ping_fake_server(false).await;
+
+        assert_eq!(result.status.version.protocol, 765);
+    }
+
+    #[tokio::test]
+    async fn invalid_packet_length() {
+        let (mut client, mut server) = duplex(64);
+        let mut data = vec![];
+        write_varint(&mut data, 0);
+        server.write_all(&data).await.unwrap();
+
+        assert!(read_packet(&mut client).await.is_err());
+    }
+}
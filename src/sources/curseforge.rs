@@ -3,7 +3,10 @@ use std::{borrow::Cow, collections::BTreeMap, env};
 use anyhow::{anyhow, Result};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
-use crate::app::{App, CacheStrategy, ResolvedFile};
+use crate::{
+    app::{App, CacheStrategy, ResolvedFile},
+    model::{Dependency, Downloadable},
+};
 
 static CURSEFORGE_API: &str = "https://api.curseforge.com/v1";
 
@@ -40,6 +43,16 @@ pub struct CurseForgeFileDependency {
     pub relation_type: u8,
 }
 
+const RELATION_REQUIRED: u8 = 3;
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CurseForgeMod {
+    pub id: u64,
+    pub name: String,
+    pub slug: String,
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CurseForgePagination {
@@ -174,6 +187,41 @@ impl CurseForgeAPI<'_> {
         }
     }
 
+    pub async fn fetch_mod(&self, id: &str) -> Result<CurseForgeMod> {
+        let response: CurseForgeResponse<CurseForgeMod> =
+            self.fetch_api(&format!("{CURSEFORGE_API}/mods/{id}")).await?;
+
+        Ok(response.data)
+    }
+
+    pub async fn required_dependencies(&self, id: &str, version: &str) -> Result<Vec<Dependency>> {
+        let file = self.fetch_version(id, version).await?;
+
+        let mut deps = vec![];
+        for dep in file
+            .dependencies
+            .iter()
+            .filter(|d| d.relation_type == RELATION_REQUIRED)
+        {
+            let mod_id = dep.mod_id.to_string();
+            let name = self
+                .fetch_mod(&mod_id)
+                .await
+                .map_or(mod_id.clone(), |m| m.name);
+
+            deps.push(Dependency {
+                name,
+                ids: vec![mod_id.clone()],
+                downloadable: Some(Downloadable::CurseForge {
+                    id: mod_id,
+                    version: "latest".to_owned(),
+                }),
+            });
+        }
+
+        Ok(deps)
+    }
+
     fn convert_hashes(hashes: &[CurseForgeHash]) -> BTreeMap<String, String> {
         let mut result = BTreeMap::new();
         for hash in hashes {
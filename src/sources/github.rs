@@ -12,7 +12,11 @@ use reqwest::{
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use tokio::time::sleep;
 
-use crate::app::{App, CacheStrategy, ResolvedFile};
+use crate::{
+    app::{App, CacheStrategy, ResolvedFile},
+    model::SoftwareType,
+    sources::{header_number, is_ratelimited, RATELIMIT_FALLBACK_WAIT, RATELIMIT_RETRIES},
+};
 
 pub trait GithubRequestExt {
     fn with_token(self, token: Option<String>) -> Self;
@@ -29,28 +33,32 @@ impl GithubRequestExt for reqwest::RequestBuilder {
 }
 
 pub trait GithubWaitRatelimit<T> {
-    async fn wait_ratelimit(self) -> Result<T>;
+    /// `headroom` is the amount of requests that might be in flight at once
+    async fn wait_ratelimit(self, headroom: usize) -> Result<T>;
 }
 
 impl GithubWaitRatelimit<reqwest::Response> for reqwest::Response {
-    async fn wait_ratelimit(self) -> Result<Self> {
-        Ok(match self.headers().get("x-ratelimit-remaining") {
-            Some(h) => {
-                if String::from_utf8_lossy(h.as_bytes()) == "1" {
-                    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
-                    let ratelimit_reset =
-                        String::from_utf8_lossy(self.headers()["x-ratelimit-reset"].as_bytes())
-                            .parse::<u64>()?;
-                    let amount = ratelimit_reset - now;
-                    println!(" (!) Ratelimit exceeded. sleeping for {amount} seconds...");
-                    sleep(Duration::from_secs(amount)).await;
-                }
+    async fn wait_ratelimit(self, headroom: usize) -> Result<Self> {
+        // don't wait if the headers can't be parsed
+        let remaining = header_number(&self, "x-ratelimit-remaining");
+        let reset = header_number(&self, "x-ratelimit-reset");
+
+        let amount = if let Some(amount) = header_number(&self, "retry-after") {
+            // secondary ratelimits only tell how long to wait
+            Some(amount)
+        } else if let (Some(remaining), Some(reset)) = (remaining, reset) {
+            let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+            (remaining <= headroom.max(1) as u64).then(|| reset.saturating_sub(now))
+        } else {
+            is_ratelimited(&self).then_some(RATELIMIT_FALLBACK_WAIT)
+        };
 
-                self
-            }
+        if let Some(amount) = amount {
+            println!(" (!) Ratelimit exceeded. sleeping for {amount} seconds...");
+            sleep(Duration::from_secs(amount)).await;
+        }
 
-            None => self.error_for_status()?,
-        })
+        Ok(self)
     }
 }
 
@@ -72,6 +80,9 @@ pub struct GithubAsset {
     pub url: String,
     pub name: String,
     pub size: u64,
+    /// e.g. `sha256:abcdef...`, only present on newer releases
+    #[serde(default)]
+    pub digest: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -105,26 +116,33 @@ impl GithubAPI<'_> {
             HeaderValue::from_str(GITHUB_API_VERSION)?,
         );
 
-        let response = self
-            .0
-            .http_client
-            .get(format!("{}/{url}", self.0.config.sources.github.api_url))
-            .with_token(self.0.config.sources.github.api_token.clone())
-            .headers(headers)
-            .send()
-            .await?;
+        let mut retries = 0;
+        let response = loop {
+            let response = self
+                .0
+                .http_client
+                .get(format!("{}/{url}", self.0.config.sources.github.api_url))
+                .with_token(self.0.config.sources.github.api_token.clone())
+                .headers(headers.clone())
+                .send()
+                .await?
+                .wait_ratelimit(self.0.config.downloads.parallel)
+                .await?;
+
+            if is_ratelimited(&response) && retries < RATELIMIT_RETRIES {
+                retries += 1;
+                continue;
+            }
+
+            break response;
+        };
 
         if response.status() == StatusCode::NOT_MODIFIED {
             Ok(cached_data.unwrap().data)
         } else {
             let etag = response.headers().get("etag").cloned();
 
-            let json: T = response
-                .error_for_status()?
-                .wait_ratelimit()
-                .await?
-                .json()
-                .await?;
+            let json: T = response.error_for_status()?.json().await?;
 
             if let Some(etag) = etag {
                 if let Some(cache) = self.0.get_cache(CACHE_DIR) {
@@ -183,15 +201,58 @@ impl GithubAPI<'_> {
         Ok(release.clone())
     }
 
-    pub async fn fetch_asset(
+    /// Newest release with an asset matching `asset_name`, for `mcman update`
+    ///
+    /// Repositories often publish releases for several Minecraft versions or loaders, so
+    /// releases that mention the Minecraft version, then the loader, are preferred
+    pub async fn fetch_latest_release(
         &self,
         repo: &str,
-        release_tag: &str,
         asset_name: &str,
-    ) -> Result<(GithubRelease, GithubAsset)> {
-        let release = self.fetch_release(repo, release_tag).await?;
+    ) -> Result<GithubRelease> {
+        let releases = self.fetch_releases(repo).await?;
+
+        let mut candidates = releases
+            .iter()
+            .filter_map(|r| Some((r, self.find_asset(r, asset_name)?)))
+            .collect::<Vec<_>>();
+
+        let mut preferred = vec![];
+        if self.0.server.jar.get_software_type() != SoftwareType::Proxy {
+            preferred.push(self.0.mc_version());
+        }
+        preferred.extend(self.0.server.jar.get_modrinth_name());
+
+        for word in preferred {
+            let matching = candidates
+                .iter()
+                .copied()
+                .filter(|(r, a)| {
+                    [&r.tag_name, &r.name, &a.name]
+                        .iter()
+                        .any(|s| mentions(s, word))
+                })
+                .collect::<Vec<_>>();
+
+            if !matching.is_empty() {
+                candidates = matching;
+            }
+        }
 
-        let asset = match asset_name {
+        let (release, _) = candidates.first().ok_or(anyhow!(
+            "No release of repository '{repo}' has an asset matching '{asset_name}'"
+        ))?;
+
+        Ok((*release).clone())
+    }
+
+    /// The asset of a release matching `asset_name`, which can use `${tag}` and `${mcver}`
+    fn find_asset<'r>(
+        &self,
+        release: &'r GithubRelease,
+        asset_name: &str,
+    ) -> Option<&'r GithubAsset> {
+        match asset_name {
             "" | "first" | "any" => release.assets.first(),
             id => {
                 let id = if id.contains('$') {
@@ -210,10 +271,23 @@ impl GithubAPI<'_> {
                     .or(release.assets.iter().find(|a| a.name.contains(&id)))
             }
         }
-        .ok_or(anyhow!(
-            "Github release asset '{asset_name}' on release '{}' ('{release_tag}') of repository '{repo}' not found",
-            release.tag_name
-        ))?.clone();
+    }
+
+    pub async fn fetch_asset(
+        &self,
+        repo: &str,
+        release_tag: &str,
+        asset_name: &str,
+    ) -> Result<(GithubRelease, GithubAsset)> {
+        let release = self.fetch_release(repo, release_tag).await?;
+
+        let asset = self
+            .find_asset(&release, asset_name)
+            .ok_or(anyhow!(
+                "Github release asset '{asset_name}' on release '{}' ('{release_tag}') of repository '{repo}' not found",
+                release.tag_name
+            ))?
+            .clone();
 
         Ok((release, asset))
     }
@@ -226,20 +300,79 @@ impl GithubAPI<'_> {
     ) -> Result<ResolvedFile> {
         let (release, asset) = self.fetch_asset(repo, release_tag, asset_name).await?;
 
-        let cached_file_path = format!("{repo}/releases/{}/{}", release.tag_name, asset.name);
+        let hashes = asset
+            .digest
+            .as_ref()
+            .and_then(|d| d.split_once(':'))
+            .map(|(name, hash)| BTreeMap::from([(name.to_owned(), hash.to_owned())]))
+            .unwrap_or_default();
 
         Ok(ResolvedFile {
             url: format!(
                 "https://github.com/{repo}/releases/download/{}/{}",
                 release.tag_name, asset.name
             ),
-            filename: asset.name,
-            cache: CacheStrategy::File {
+            filename: asset.name.clone(),
+            // same as the path files were cached at before, see `Cache::migrate_legacy_file`
+            cache: CacheStrategy::Indexed {
                 namespace: Cow::Borrowed(CACHE_DIR),
-                path: cached_file_path,
+                key: format!("{repo}/releases/{}/{}", release.tag_name, asset.name),
             },
             size: Some(asset.size),
-            hashes: BTreeMap::new(),
+            hashes,
         })
     }
 }
+
+/// Whether `word` appears on its own in `text`, ignoring case
+///
+/// `1.20` isn't mentioned by `1.20.1` and `forge` isn't mentioned by `neoforge`
+fn mentions(text: &str, word: &str) -> bool {
+    let text = text.to_lowercase();
+    let word = word.to_lowercase();
+    let (Some(first), Some(last)) = (word.chars().next(), word.chars().next_back()) else {
+        return false;
+    };
+
+    text.match_indices(&word).any(|(i, _)| {
+        let before = text[..i].chars().next_back();
+        let mut after = text[i + word.len()..].chars();
+
+        let separated_before = match before {
+            None => true,
+            Some(c) if first.is_ascii_digit() => !c.is_ascii_digit() && c != '.',
+            Some(c) => !c.is_alphabetic(),
+        };
+        let separated_after = match after.next() {
+            None => true,
+            Some('.') if last.is_ascii_digit() => !after.next().is_some_and(|c| c.is_ascii_digit()),
+            Some(c) if last.is_ascii_digit() => !c.is_ascii_digit(),
+            Some(c) => !c.is_alphabetic(),
+        };
+
+        separated_before && separated_after
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mentioned_versions() {
+        assert!(mentions("sodium-fabric-mc1.20.1-0.5.3.jar", "1.20.1"));
+        assert!(mentions("v2.0.0+1.20.1", "1.20.1"));
+        assert!(mentions("For 1.20.", "1.20"));
+        assert!(!mentions("1.20.1", "1.20"));
+        assert!(!mentions("11.20.1", "1.20.1"));
+        assert!(!mentions("1.20.10", "1.20.1"));
+    }
+
+    #[test]
+    fn mentioned_loaders() {
+        assert!(mentions("Create-Fabric-0.5.1.jar", "fabric"));
+        assert!(mentions("mod-1.20.1-forge.jar", "forge"));
+        assert!(!mentions("mod-1.20.1-neoforge.jar", "forge"));
+        assert!(!mentions("mod.jar", ""));
+    }
+}
@@ -10,7 +10,7 @@ use thiserror::Error;
 
 use crate::{
     app::{App, CacheStrategy, ResolvedFile},
-    model::ServerType,
+    model::{Dependency, Downloadable, ServerType},
 };
 
 const API_V1: &str = "https://hangar.papermc.io/api/v1";
@@ -451,6 +451,35 @@ impl HangarAPI<'_> {
         }
     }
 
+    /// Required plugin dependencies for the platform of the server
+    pub async fn required_dependencies(&self, id: &str, version: &str) -> Result<Vec<Dependency>> {
+        let version = self
+            .fetch_hangar_version(id, version)
+            .await
+            .context("Fetching project version")?;
+
+        Ok(version
+            .plugin_dependencies
+            .get(&self.get_platform().unwrap_or(Platform::Paper))
+            .into_iter()
+            .flatten()
+            .filter(|dep| dep.required)
+            .map(|dep| Dependency {
+                name: dep.name.clone(),
+                ids: vec![dep.name.clone()],
+                // external dependencies aren't on hangar
+                downloadable: if dep.external_url.is_some() {
+                    None
+                } else {
+                    Some(Downloadable::Hangar {
+                        id: dep.name.clone(),
+                        version: "latest".to_owned(),
+                    })
+                },
+            })
+            .collect())
+    }
+
     #[allow(clippy::cast_sign_loss)]
     pub async fn resolve_source(&self, id: &str, version: &str) -> Result<ResolvedFile> {
         let version = self
@@ -14,3 +14,25 @@ pub mod purpur;
 pub mod quilt;
 pub mod spigot;
 pub mod vanilla;
+
+use reqwest::StatusCode;
+
+/// How often a request is sent again after it was rejected by a ratelimit
+pub const RATELIMIT_RETRIES: usize = 3;
+/// Seconds to wait after hitting a ratelimit that doesn't say when it resets
+pub const RATELIMIT_FALLBACK_WAIT: u64 = 60;
+
+/// A numeric header like `x-ratelimit-remaining`, `None` if it's missing or malformed
+pub fn header_number(res: &reqwest::Response, name: &str) -> Option<u64> {
+    res.headers().get(name)?.to_str().ok()?.trim().parse().ok()
+}
+
+/// Whether the request was rejected by a ratelimit, `429 Too Many Requests` or
+/// github's `403 Forbidden` with no remaining requests
+pub fn is_ratelimited(res: &reqwest::Response) -> bool {
+    match res.status() {
+        StatusCode::TOO_MANY_REQUESTS => true,
+        StatusCode::FORBIDDEN => header_number(res, "x-ratelimit-remaining") == Some(0),
+        _ => false,
+    }
+}
@@ -6,7 +6,8 @@ use tokio::time::sleep;
 
 use crate::{
     app::{App, CacheStrategy, ResolvedFile},
-    model::{ServerType, SoftwareType},
+    model::{Dependency, Downloadable, ServerType, SoftwareType},
+    sources::{header_number, is_ratelimited, RATELIMIT_FALLBACK_WAIT, RATELIMIT_RETRIES},
 };
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -104,26 +105,31 @@ pub struct ModrinthFile {
 }
 
 pub trait ModrinthWaitRatelimit<T> {
-    async fn wait_ratelimit(self) -> Result<T>;
+    /// `headroom` is the amount of requests that might be in flight at once
+    async fn wait_ratelimit(self, headroom: usize) -> Result<T>;
 }
 
 impl ModrinthWaitRatelimit<reqwest::Response> for reqwest::Response {
-    async fn wait_ratelimit(self) -> Result<Self> {
-        let res = if let Some(h) = self.headers().get("x-ratelimit-remaining") {
-            if String::from_utf8_lossy(h.as_bytes()) == "1" {
-                let ratelimit_reset =
-                    String::from_utf8_lossy(self.headers()["x-ratelimit-reset"].as_bytes())
-                        .parse::<u64>()?;
-                let amount = ratelimit_reset;
-                println!(" (!) Ratelimit exceeded. sleeping for {amount} seconds...");
-                sleep(Duration::from_secs(amount)).await;
-            }
-            self
+    async fn wait_ratelimit(self, headroom: usize) -> Result<Self> {
+        // don't wait if the headers can't be parsed
+        let remaining = header_number(&self, "x-ratelimit-remaining");
+        let reset = header_number(&self, "x-ratelimit-reset");
+
+        // modrinth sends the seconds until the reset
+        let amount = if let (Some(remaining), Some(reset)) = (remaining, reset) {
+            (remaining <= headroom.max(1) as u64).then_some(reset)
+        } else if is_ratelimited(&self) {
+            Some(header_number(&self, "retry-after").unwrap_or(RATELIMIT_FALLBACK_WAIT))
         } else {
-            self.error_for_status()?
+            None
         };
 
-        Ok(res)
+        if let Some(amount) = amount {
+            println!(" (!) Ratelimit exceeded. sleeping for {amount} seconds...");
+            sleep(Duration::from_secs(amount)).await;
+        }
+
+        Ok(self)
     }
 }
 
@@ -133,17 +139,26 @@ static API_URL: &str = "https://api.modrinth.com/v2";
 
 impl ModrinthAPI<'_> {
     pub async fn fetch_api<T: DeserializeOwned>(&self, url: &str) -> Result<T> {
-        let json: T = self
-            .0
-            .http_client
-            .get(url)
-            .send()
-            .await?
-            .error_for_status()?
-            .wait_ratelimit()
-            .await?
-            .json()
-            .await?;
+        let mut retries = 0;
+        let response = loop {
+            let response = self
+                .0
+                .http_client
+                .get(url)
+                .send()
+                .await?
+                .wait_ratelimit(self.0.config.downloads.parallel)
+                .await?;
+
+            if is_ratelimited(&response) && retries < RATELIMIT_RETRIES {
+                retries += 1;
+                continue;
+            }
+
+            break response;
+        };
+
+        let json: T = response.error_for_status()?.json().await?;
 
         Ok(json)
     }
@@ -152,6 +167,22 @@ impl ModrinthAPI<'_> {
         self.fetch_api(&format!("{API_URL}/project/{id}")).await
     }
 
+    pub async fn fetch_projects(&self, ids: &[String]) -> Result<Vec<ModrinthProject>> {
+        self.fetch_api(
+            reqwest::Url::parse_with_params(
+                &format!("{API_URL}/projects"),
+                &[("ids", serde_json::to_string(ids)?)],
+            )?
+            .as_str(),
+        )
+        .await
+    }
+
+    pub async fn fetch_version_by_id(&self, version_id: &str) -> Result<ModrinthVersion> {
+        self.fetch_api(&format!("{API_URL}/version/{version_id}"))
+            .await
+    }
+
     pub async fn fetch_all_versions(&self, id: &str) -> Result<Vec<ModrinthVersion>> {
         self.fetch_api(&format!("{API_URL}/project/{id}/version"))
             .await
@@ -291,17 +322,65 @@ impl ModrinthAPI<'_> {
         .await
     }
 
+    /// Required dependencies of a version, pinned to the version it depends on if it has one
+    pub async fn required_dependencies(&self, id: &str, version: &str) -> Result<Vec<Dependency>> {
+        let version = self.fetch_version(id, version).await?;
+
+        let mut deps = vec![];
+        for dep in &version.dependencies {
+            if dep.dependency_type != Some(DependencyType::Required) {
+                continue;
+            }
+
+            let project_id = match (&dep.project_id, &dep.version_id) {
+                (Some(project_id), _) => project_id.clone(),
+                (None, Some(version_id)) => self.fetch_version_by_id(version_id).await?.project_id,
+                // only a file name, can't be added
+                (None, None) => continue,
+            };
+
+            deps.push((project_id, dep.version_id.clone()));
+        }
+
+        if deps.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let projects = self
+            .fetch_projects(&deps.iter().map(|(id, _)| id.clone()).collect::<Vec<_>>())
+            .await?;
+
+        Ok(deps
+            .into_iter()
+            .map(|(project_id, version_id)| {
+                let project = projects.iter().find(|p| p.id == project_id);
+
+                Dependency {
+                    name: project.map_or(project_id.clone(), |p| p.title.clone()),
+                    ids: project.map_or(vec![project_id.clone()], |p| {
+                        vec![p.id.clone(), p.slug.clone()]
+                    }),
+                    downloadable: Some(Downloadable::Modrinth {
+                        id: project.map_or(project_id.clone(), |p| p.slug.clone()),
+                        version: version_id.unwrap_or("latest".to_owned()),
+                    }),
+                }
+            })
+            .collect())
+    }
+
     pub async fn resolve_source(&self, id: &str, version: &str) -> Result<ResolvedFile> {
         let (file, version) = self.fetch_file(id, version).await?;
 
-        let cached_file_path = format!("{id}/{}/{}", version.id, file.filename);
+        // same as the path files were cached at before, see `Cache::migrate_legacy_file`
+        let key = format!("{id}/{}/{}", version.id, file.filename);
 
         Ok(ResolvedFile {
             url: file.url,
             filename: file.filename,
-            cache: CacheStrategy::File {
+            cache: CacheStrategy::Indexed {
                 namespace: Cow::Borrowed("modrinth"),
-                path: cached_file_path,
+                key,
             },
             size: Some(file.size),
             hashes: file.hashes,
@@ -1,11 +1,12 @@
 use crate::app::{App, CacheStrategy, ResolvedFile};
-use anyhow::{anyhow, Context, Result};
+use anyhow::{anyhow, bail, Context, Result};
 use serde::{Deserialize, Serialize};
 use std::{borrow::Cow, collections::{BTreeMap, HashMap}};
 
 pub struct VanillaAPI<'a>(pub &'a App);
 
 pub const CACHE_DIR: &str = "vanilla";
+const MANIFEST_CACHE_PATH: &str = "version_manifest.json";
 pub const VERSION_MANIFEST_URL: &str =
     "https://piston-meta.mojang.com/mc/game/version_manifest_v2.json";
 
@@ -194,18 +195,6 @@ impl VersionManifest {
         self.versions.iter().find(|v| v.id == id).cloned()
     }
 
-    /// Fetch the latest release's `VersionInfo`
-    pub async fn fetch_latest_release(&self, client: &reqwest::Client) -> Result<VersionInfo> {
-        let id = self.latest.release.clone();
-        self.fetch(&id, client).await
-    }
-
-    /// Fetch the latest snapshot's `VersionInfo`
-    pub async fn fetch_latest_snapshot(&self, client: &reqwest::Client) -> Result<VersionInfo> {
-        let id = self.latest.snapshot.clone();
-        self.fetch(&id, client).await
-    }
-
     /// Fetch the `VersionInfo` of id
     pub async fn fetch(&self, id: &str, client: &reqwest::Client) -> Result<VersionInfo> {
         self.find(id)
@@ -274,33 +263,88 @@ pub async fn fetch_version_manifest(client: &reqwest::Client) -> Result<VersionM
 }
 
 impl VanillaAPI<'_> {
+    /// Fetch the version manifest, it's cached for offline builds
+    pub async fn fetch_manifest(&self) -> Result<VersionManifest> {
+        let cache = self.0.get_cache(CACHE_DIR);
+
+        if self.0.config.offline {
+            return cache
+                .map(|c| c.try_get_json(MANIFEST_CACHE_PATH))
+                .transpose()?
+                .flatten()
+                .context("The version manifest isn't cached, can't fetch it in offline mode");
+        }
+
+        let manifest = fetch_version_manifest(&self.0.http_client).await?;
+
+        if let Some(cache) = cache {
+            cache.write_json(MANIFEST_CACHE_PATH, &manifest)?;
+        }
+
+        Ok(manifest)
+    }
+
+    /// Fetch the `VersionInfo` of a version id, these never change so they are cached
+    pub async fn fetch_version_info(&self, id: &str) -> Result<VersionInfo> {
+        if let Some(info) = self.get_cached_version_info(id) {
+            return Ok(info);
+        }
+
+        let manifest = self.fetch_manifest().await?;
+        self.fetch_version_info_from(&manifest, id).await
+    }
+
+    fn get_cached_version_info(&self, id: &str) -> Option<VersionInfo> {
+        self.0
+            .get_cache(CACHE_DIR)?
+            .try_get_json(&format!("versions/{id}.json"))
+            .ok()
+            .flatten()
+    }
+
+    async fn fetch_version_info_from(
+        &self,
+        manifest: &VersionManifest,
+        id: &str,
+    ) -> Result<VersionInfo> {
+        if let Some(info) = self.get_cached_version_info(id) {
+            return Ok(info);
+        }
+
+        if self.0.config.offline {
+            bail!("Version info of '{id}' isn't cached, can't fetch it in offline mode");
+        }
+
+        let info = manifest.fetch(id, &self.0.http_client).await?;
+
+        if let Some(cache) = self.0.get_cache(CACHE_DIR) {
+            cache.write_json(&format!("versions/{id}.json"), &info)?;
+        }
+
+        Ok(info)
+    }
+
     pub async fn fetch_latest_mcver(&self) -> Result<String> {
-        Ok(fetch_version_manifest(&self.0.http_client)
-            .await?
-            .latest
-            .release)
+        Ok(self.fetch_manifest().await?.latest.release)
     }
 
     pub async fn resolve_source(&self, version: &str) -> Result<ResolvedFile> {
-        let version_manifest = fetch_version_manifest(&self.0.http_client)
+        let version_manifest = self
+            .fetch_manifest()
             .await
             .context("Fetching version manifest")?;
 
-        let version = match version {
-            "latest" => version_manifest
-                .fetch_latest_release(&self.0.http_client)
-                .await
-                .context("Fetching latest release")?,
-            "latest-snapshot" => version_manifest
-                .fetch_latest_snapshot(&self.0.http_client)
-                .await
-                .context("Fetching latest snapshot")?,
-            id => version_manifest
-                .fetch(id, &self.0.http_client)
-                .await
-                .context(format!("Fetching release {id}"))?,
+        let id = match version {
+            "latest" => version_manifest.latest.release.clone(),
+            "latest-snapshot" => version_manifest.latest.snapshot.clone(),
+            id => id.to_owned(),
         };
 
+        let version = self
+            .fetch_version_info_from(&version_manifest, &id)
+            .await
+            .context(format!("Fetching release {id}"))?;
+
         let file = version.downloads.get(&DownloadType::Server).ok_or(anyhow!(
             "version manifest doesn't include a server download"
         ))?;
@@ -67,6 +67,7 @@ pub fn write_gitignore() -> Result<PathBuf> {
         ("**/server", "# mcman: Exclude mcman build outputs"),
         ("*.mrpack", "# mcman: Exclude exported mrpacks"),
         ("**/.env", "# mcman: Exclude local dotenv files"),
+        ("**/secrets.toml", "# mcman: Exclude decrypted secrets"),
     ] {
         if !list.contains(&ignore) {
             if !comment.is_empty() {
@@ -131,9 +132,9 @@ where
     run_command("git", args)
 }
 
-pub fn write_dockerfile(folder: &Path) -> Result<()> {
+pub fn write_dockerfile(folder: &Path, content: &str) -> Result<()> {
     let mut f = File::create(folder.join("Dockerfile"))?;
-    f.write_all(include_bytes!("../../res/default_dockerfile"))?;
+    f.write_all(content.as_bytes())?;
     Ok(())
 }
 
@@ -1,6 +1,6 @@
-use std::{borrow::Cow, cmp::Ordering, fmt::Display};
+use std::{borrow::Cow, cmp::Ordering, fmt::Display, time::Duration};
 
-use anyhow::Result;
+use anyhow::{anyhow, bail, Result};
 use lazy_static::lazy_static;
 use regex::Regex;
 
@@ -68,6 +68,63 @@ pub fn url_to_folder(url: &str) -> String {
         .replace(' ', "-")
 }
 
+/// Parses durations like `30d`, `12h`, `2w` or `90s`, the unit is required
+pub fn parse_duration(s: &str) -> Result<Duration> {
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (num, unit) = s.split_at(split);
+
+    let num = num
+        .parse::<u64>()
+        .map_err(|_| anyhow!("Invalid duration '{s}', expected something like '30d'"))?;
+
+    let secs = match unit.trim() {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 60 * 60 * 24,
+        "w" => 60 * 60 * 24 * 7,
+        "" => bail!("Missing unit in duration '{s}', use one of s, m, h, d, w (like '{s}d')"),
+        u => bail!("Unknown duration unit '{u}', use one of s, m, h, d, w"),
+    };
+
+    let secs = num
+        .checked_mul(secs)
+        .ok_or_else(|| anyhow!("Duration '{s}' is too long"))?;
+
+    Ok(Duration::from_secs(secs))
+}
+
+/// Parses sizes like `5G`, `500MB` or `1.5GiB` into bytes (1024-based)
+pub fn parse_size(s: &str) -> Result<u64> {
+    let s = s.trim();
+    let split = s
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(s.len());
+    let (num, unit) = s.split_at(split);
+
+    let num = num
+        .parse::<f64>()
+        .map_err(|_| anyhow!("Invalid size '{s}', expected something like '5G'"))?;
+
+    let unit = unit.trim().to_uppercase();
+    let multiplier = match unit.trim_end_matches('B').trim_end_matches('I') {
+        "" => 1u64,
+        "K" => 1 << 10,
+        "M" => 1 << 20,
+        "G" => 1 << 30,
+        "T" => 1 << 40,
+        _ => bail!("Unknown size unit '{unit}', use one of K, M, G, T"),
+    };
+
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        clippy::cast_precision_loss
+    )]
+    Ok((num * multiplier as f64) as u64)
+}
+
 static SANITIZE_R1: &str = "<(?:\"[^\"]*\"['\"]*|'[^']*'['\"]*|[^'\">])+>";
 
 pub fn sanitize(s: &str) -> Result<String> {
@@ -83,6 +140,19 @@ pub fn sanitize(s: &str) -> Result<String> {
 
 lazy_static! {
     static ref DOLLAR_REGEX: Regex = Regex::new(r"\$\{(\w+)?\}").unwrap();
+    /// Bootstrapped files also support `${KEY:-default}` and `${secret:NAME}`,
+    /// other `${a:b}` (like log4j lookups) are left alone
+    static ref BOOTSTRAP_REGEX: Regex =
+        Regex::new(r"\$\{(secret:\w+|\w+:-[^{}\n]*|\w+)?\}").unwrap();
+}
+
+/// Keys of every "${}" a bootstrapped file uses
+pub fn bootstrap_keys(input: &str) -> Vec<&str> {
+    BOOTSTRAP_REGEX
+        .captures_iter(input)
+        .filter_map(|caps| caps.get(1))
+        .map(|m| m.as_str())
+        .collect()
 }
 
 /// Utility fn for replacing strings containing "${}"
@@ -90,7 +160,22 @@ pub fn dollar_repl<F>(input: &str, replacer: F) -> String
 where
     F: Fn(&str) -> Option<String>,
 {
-    DOLLAR_REGEX
+    regex_repl(&DOLLAR_REGEX, input, replacer)
+}
+
+/// Like [`dollar_repl`], with the syntax of bootstrapped files
+pub fn bootstrap_repl<F>(input: &str, replacer: F) -> String
+where
+    F: Fn(&str) -> Option<String>,
+{
+    regex_repl(&BOOTSTRAP_REGEX, input, replacer)
+}
+
+fn regex_repl<F>(regex: &Regex, input: &str, replacer: F) -> String
+where
+    F: Fn(&str) -> Option<String>,
+{
+    regex
         .replace_all(input, |caps: &regex::Captures| {
             let var_name = caps.get(1).map(|v| v.as_str()).unwrap_or_default();
 
@@ -102,3 +187,62 @@ where
         })
         .into_owned()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn durations() {
+        assert_eq!(parse_duration("90s").unwrap(), Duration::from_secs(90));
+        assert_eq!(
+            parse_duration("12h").unwrap(),
+            Duration::from_secs(12 * 60 * 60)
+        );
+        assert_eq!(
+            parse_duration(" 2w ").unwrap(),
+            Duration::from_secs(14 * 24 * 60 * 60)
+        );
+    }
+
+    #[test]
+    fn invalid_durations() {
+        assert!(parse_duration("30").is_err());
+        assert!(parse_duration("30y").is_err());
+        assert!(parse_duration("d").is_err());
+        assert!(parse_duration("99999999999999999w").is_err());
+        assert!(parse_duration("99999999999999999999999s").is_err());
+    }
+
+    #[test]
+    fn bootstrap_key_extraction() {
+        assert_eq!(
+            bootstrap_keys("motd=${MOTD:-A Minecraft Server}\nport=${PORT}\npass=${secret:RCON}"),
+            ["MOTD:-A Minecraft Server", "PORT", "secret:RCON"]
+        );
+        assert!(bootstrap_keys("${} ${sys:user.home} ${env:HOME} ${ctx:a.b}").is_empty());
+    }
+
+    #[test]
+    fn replacing() {
+        let replacer = |key: &str| match key {
+            "PORT" => Some("25565".to_owned()),
+            "secret:RCON" => Some("hunter2".to_owned()),
+            _ => None,
+        };
+
+        assert_eq!(
+            bootstrap_repl("${PORT} ${secret:RCON} ${MISSING} ${}", replacer),
+            "25565 hunter2 ${MISSING} ${}"
+        );
+        // log4j lookups are left alone
+        assert_eq!(
+            bootstrap_repl("${sys:user.home}/${env:HOME}", replacer),
+            "${sys:user.home}/${env:HOME}"
+        );
+        assert_eq!(
+            dollar_repl("${PORT} ${secret:RCON} ${sys:user.home}", replacer),
+            "25565 ${secret:RCON} ${sys:user.home}"
+        );
+    }
+}